@@ -0,0 +1,188 @@
+// Splitting instructor availability into bookable slots
+use super::response::{AvailabilityData, AvailabilitySlot, Commitment, InnexgoHoursError};
+use std::future::Future;
+
+// every slot of an availability, ignoring whether it has been claimed
+// the server answers AvailabilitySlotViewProps with these, less the claimed ones
+// a trailing remainder shorter than slot_duration is not a slot
+pub fn availability_slots(availability_data: &AvailabilityData) -> Vec<AvailabilitySlot> {
+  if !availability_data.active || availability_data.slot_duration <= 0 {
+    return vec![];
+  }
+
+  let mut slots = vec![];
+  let mut start_time = availability_data.start_time;
+  while start_time + availability_data.slot_duration <= availability_data.end_time {
+    slots.push(AvailabilitySlot {
+      availability: availability_data.availability.clone(),
+      start_time,
+      end_time: start_time + availability_data.slot_duration,
+    });
+    start_time += availability_data.slot_duration;
+  }
+  slots
+}
+
+// the slots lying entirely within the requested window, earliest first
+pub fn fitting_slots(
+  slots: &[AvailabilitySlot],
+  start_time: i64,
+  end_time: i64,
+) -> Vec<AvailabilitySlot> {
+  let mut fitting: Vec<AvailabilitySlot> = slots
+    .iter()
+    .filter(|slot| slot.start_time >= start_time && slot.end_time <= end_time)
+    .cloned()
+    .collect();
+  fitting.sort_by_key(|slot| slot.start_time);
+  fitting
+}
+
+// claims each slot in turn until one succeeds, skipping those claimed first by someone else
+// None if every slot was taken, in which case InnexgoHoursService::session_book files a session request
+pub async fn claim_first_slot<F, Fut>(
+  slots: Vec<AvailabilitySlot>,
+  mut claim: F,
+) -> Result<Option<Commitment>, InnexgoHoursError>
+where
+  F: FnMut(AvailabilitySlot) -> Fut,
+  Fut: Future<Output = Result<Commitment, InnexgoHoursError>>,
+{
+  for slot in slots {
+    match claim(slot).await {
+      Ok(commitment) => return Ok(Some(commitment)),
+      Err(InnexgoHoursError::AvailabilitySlotTaken) => continue,
+      Err(e) => return Err(e),
+    }
+  }
+  Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fixture;
+  use crate::response::Availability;
+
+  fn availability(availability_id: i64) -> Availability {
+    Availability {
+      availability_id,
+      creation_time: 0,
+      creator_user_id: 1,
+      course: fixture::course(1),
+    }
+  }
+
+  fn availability_data(start_time: i64, end_time: i64, slot_duration: i64) -> AvailabilityData {
+    AvailabilityData {
+      availability_data_id: 1,
+      creation_time: 0,
+      creator_user_id: 1,
+      availability: availability(1),
+      start_time,
+      end_time,
+      slot_duration,
+      active: true,
+    }
+  }
+
+  fn slot(availability_id: i64, start_time: i64, end_time: i64) -> AvailabilitySlot {
+    AvailabilitySlot {
+      availability: availability(availability_id),
+      start_time,
+      end_time,
+    }
+  }
+
+  fn times(slots: &[AvailabilitySlot]) -> Vec<(i64, i64)> {
+    slots.iter().map(|s| (s.start_time, s.end_time)).collect()
+  }
+
+  #[test]
+  fn slots_drop_the_trailing_remainder() {
+    let slots = availability_slots(&availability_data(100, 350, 100));
+    assert_eq!(times(&slots), vec![(100, 200), (200, 300)]);
+    let slots = availability_slots(&availability_data(100, 300, 100));
+    assert_eq!(times(&slots), vec![(100, 200), (200, 300)]);
+    assert!(availability_slots(&availability_data(100, 150, 100)).is_empty());
+  }
+
+  #[test]
+  fn no_slots_for_invalid_or_inactive_availability() {
+    assert!(availability_slots(&availability_data(100, 300, 0)).is_empty());
+    assert!(availability_slots(&availability_data(100, 300, -100)).is_empty());
+    let mut inactive = availability_data(100, 300, 100);
+    inactive.active = false;
+    assert!(availability_slots(&inactive).is_empty());
+  }
+
+  #[test]
+  fn fitting_slots_are_within_the_window_earliest_first() {
+    let slots = [
+      slot(2, 300, 400),
+      slot(1, 100, 200),
+      slot(3, 150, 250),
+      slot(1, 0, 100),
+      slot(1, 350, 450),
+    ];
+    let fitting = fitting_slots(&slots, 100, 400);
+    assert_eq!(times(&fitting), vec![(100, 200), (150, 250), (300, 400)]);
+  }
+
+  #[cfg(feature = "client")]
+  #[tokio::test]
+  async fn claim_first_slot_skips_taken_slots() {
+    let slots = vec![slot(1, 0, 100), slot(1, 100, 200), slot(2, 200, 300)];
+    let session_data = fixture::session_data(1, 1, 100, 200);
+
+    let mut tried = vec![];
+    let commitment = claim_first_slot(slots.clone(), |slot| {
+      tried.push(slot.start_time);
+      let result = match slot.start_time {
+        0 => Err(InnexgoHoursError::AvailabilitySlotTaken),
+        _ => Ok(fixture::commitment(2, &session_data)),
+      };
+      async { result }
+    })
+    .await
+    .unwrap();
+    assert_eq!(tried, vec![0, 100]);
+    assert_eq!(commitment.unwrap().commitment_id, 201);
+  }
+
+  #[cfg(feature = "client")]
+  #[tokio::test]
+  async fn claim_first_slot_falls_back_when_all_are_taken() {
+    let slots = vec![slot(1, 0, 100), slot(1, 100, 200)];
+    let mut tried = 0;
+    let commitment = claim_first_slot(slots, |_| {
+      tried += 1;
+      async { Err(InnexgoHoursError::AvailabilitySlotTaken) }
+    })
+    .await
+    .unwrap();
+    assert_eq!(tried, 2);
+    assert!(commitment.is_none());
+
+    assert!(
+      claim_first_slot(vec![], |_| async { Err(InnexgoHoursError::Unknown) })
+        .await
+        .unwrap()
+        .is_none()
+    );
+  }
+
+  #[cfg(feature = "client")]
+  #[tokio::test]
+  async fn claim_first_slot_stops_on_other_errors() {
+    let slots = vec![slot(1, 0, 100), slot(1, 100, 200)];
+    let mut tried = 0;
+    let result = claim_first_slot(slots, |_| {
+      tried += 1;
+      async { Err(InnexgoHoursError::Network) }
+    })
+    .await;
+    assert_eq!(tried, 1);
+    assert!(matches!(result, Err(InnexgoHoursError::Network)));
+  }
+}
@@ -0,0 +1,182 @@
+// In-process cache for entities that rarely change
+use super::secret::Secret;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// entity kinds whose views may be cached
+// the entity and its data records (eg: School and SchoolData) share a kind
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CachedEntity {
+  School,
+  Location,
+  Course,
+}
+
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+  pub school_ttl: Duration,
+  pub location_ttl: Duration,
+  pub course_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+  fn default() -> Self {
+    CacheConfig {
+      school_ttl: Duration::from_secs(10 * 60),
+      location_ttl: Duration::from_secs(5 * 60),
+      course_ttl: Duration::from_secs(5 * 60),
+    }
+  }
+}
+
+impl CacheConfig {
+  fn ttl(&self, entity: CachedEntity) -> Duration {
+    match entity {
+      CachedEntity::School => self.school_ttl,
+      CachedEntity::Location => self.location_ttl,
+      CachedEntity::Course => self.course_ttl,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CacheMetrics {
+  pub hits: u64,
+  pub misses: u64,
+}
+
+impl CacheMetrics {
+  pub fn hit_rate(&self) -> f64 {
+    match self.hits + self.misses {
+      0 => 0.0,
+      total => self.hits as f64 / total as f64,
+    }
+  }
+}
+
+struct CacheEntry {
+  inserted: Instant,
+  // the id filter of the view props, None if the view was not filtered by id
+  ids: Option<Vec<i64>>,
+  value: Value,
+}
+
+pub struct ResponseCache {
+  config: CacheConfig,
+  entries: Mutex<HashMap<(CachedEntity, String), CacheEntry>>,
+  hits: AtomicU64,
+  misses: AtomicU64,
+}
+
+impl ResponseCache {
+  pub fn new(config: CacheConfig) -> Self {
+    ResponseCache {
+      config,
+      entries: Mutex::new(HashMap::new()),
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+    }
+  }
+
+  // the key includes the endpoint, the credential sent in the Authorization header (if any)
+  // and the full props (including any api_key in them)
+  // so responses are never shared between clients authenticated as different users
+  pub fn key<Props: Serialize>(
+    endpoint: &str,
+    api_key: Option<&Secret>,
+    props: &Props,
+  ) -> Option<String> {
+    serde_json::to_string(&(api_key, props))
+      .ok()
+      .map(|key| format!("{}:{}", endpoint, key))
+  }
+
+  pub fn get<T: DeserializeOwned>(&self, entity: CachedEntity, key: &str) -> Option<T> {
+    let mut entries = self.entries.lock().unwrap();
+    let ttl = self.config.ttl(entity);
+    let cache_key = (entity, key.to_owned());
+
+    let value = match entries.get(&cache_key) {
+      Some(entry) if entry.inserted.elapsed() < ttl => {
+        serde_json::from_value(entry.value.clone()).ok()
+      }
+      Some(_) => {
+        entries.remove(&cache_key);
+        None
+      }
+      None => None,
+    };
+
+    match value {
+      Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+      None => self.misses.fetch_add(1, Ordering::Relaxed),
+    };
+    value
+  }
+
+  pub fn insert<T: Serialize>(
+    &self,
+    entity: CachedEntity,
+    key: String,
+    ids: Option<Vec<i64>>,
+    value: &T,
+  ) {
+    if let Ok(value) = serde_json::to_value(value) {
+      self.entries.lock().unwrap().insert(
+        (entity, key),
+        CacheEntry {
+          inserted: Instant::now(),
+          ids,
+          value,
+        },
+      );
+    }
+  }
+
+  // drops every cached view that could include the entity with this id
+  pub fn invalidate(&self, entity: CachedEntity, id: i64) {
+    self.entries.lock().unwrap().retain(|(e, _), entry| {
+      *e != entity
+        || match entry.ids {
+          Some(ref ids) => !ids.contains(&id),
+          None => false,
+        }
+    });
+  }
+
+  pub fn metrics(&self) -> CacheMetrics {
+    CacheMetrics {
+      hits: self.hits.load(Ordering::Relaxed),
+      misses: self.misses.load(Ordering::Relaxed),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn key_depends_on_the_header_credential() {
+    let props = serde_json::json!({"schoolId": [1], "apiKey": ""});
+    let a = Secret::from("a");
+    let b = Secret::from("b");
+    let key_a = ResponseCache::key("public/school/view", Some(&a), &props).unwrap();
+    let key_b = ResponseCache::key("public/school/view", Some(&b), &props).unwrap();
+    let key_none = ResponseCache::key("public/school/view", None, &props).unwrap();
+    assert_ne!(key_a, key_b);
+    assert_ne!(key_a, key_none);
+
+    let cache = ResponseCache::new(CacheConfig::default());
+    cache.insert(CachedEntity::School, key_a.clone(), Some(vec![1]), &"a");
+    assert_eq!(cache.get::<String>(CachedEntity::School, &key_b), None);
+    assert_eq!(
+      cache.get::<String>(CachedEntity::School, &key_a).as_deref(),
+      Some("a")
+    );
+  }
+}
@@ -0,0 +1,781 @@
+// Deciding what a user may do, shared between client and server
+use super::request::{AdminshipKind, BatchNewItemProps, Capability, CourseMembershipKind};
+
+// everything an admin may do within their school and its courses
+// other school roles also apply to every course of the school
+const ADMIN: &[Capability] = &[
+  Capability::SchoolEdit,
+  Capability::SchoolDurationManage,
+  Capability::SchoolKeyManage,
+  Capability::AdminshipManage,
+  Capability::LocationManage,
+  Capability::WebhookManage,
+  Capability::DeviceManage,
+  Capability::CourseCreate,
+  Capability::CourseEdit,
+  Capability::CourseKeyManage,
+  Capability::CourseMembershipManage,
+  Capability::SessionManage,
+  Capability::AvailabilityManage,
+  Capability::SessionRequestRespond,
+  Capability::CommitmentManage,
+  Capability::EncounterCreate,
+  Capability::StayManage,
+  Capability::CourseView,
+  Capability::AttendanceView,
+];
+
+const FRONT_OFFICE: &[Capability] = &[
+  Capability::LocationManage,
+  Capability::DeviceManage,
+  Capability::EncounterCreate,
+  Capability::StayManage,
+  Capability::CourseView,
+  Capability::AttendanceView,
+];
+
+const COUNSELOR: &[Capability] = &[Capability::CourseView, Capability::AttendanceView];
+
+const INSTRUCTOR: &[Capability] = &[
+  Capability::CourseEdit,
+  Capability::CourseKeyManage,
+  Capability::CourseMembershipManage,
+  Capability::SessionManage,
+  Capability::AvailabilityManage,
+  Capability::SessionRequestRespond,
+  Capability::CommitmentManage,
+  Capability::EncounterCreate,
+  Capability::StayManage,
+  Capability::CourseView,
+  Capability::AttendanceView,
+];
+
+const TEACHING_ASSISTANT: &[Capability] = &[
+  Capability::SessionManage,
+  Capability::SessionRequestRespond,
+  Capability::CommitmentManage,
+  Capability::EncounterCreate,
+  Capability::StayManage,
+  Capability::CourseView,
+  Capability::AttendanceView,
+];
+
+const OBSERVER: &[Capability] = &[Capability::CourseView, Capability::AttendanceView];
+
+const STUDENT: &[Capability] = &[
+  Capability::SessionRequestCreate,
+  Capability::CommitmentCreateSelf,
+  Capability::CourseView,
+];
+
+pub fn school_capabilities(adminship_kind: &AdminshipKind) -> Vec<Capability> {
+  match adminship_kind {
+    AdminshipKind::Admin => ADMIN.to_vec(),
+    AdminshipKind::FrontOffice => FRONT_OFFICE.to_vec(),
+    AdminshipKind::Counselor => COUNSELOR.to_vec(),
+    AdminshipKind::Cancel => vec![],
+  }
+}
+
+// adminship_kind is that of the user in the course's school, if any
+pub fn course_capabilities(
+  adminship_kind: Option<&AdminshipKind>,
+  course_membership_kind: Option<&CourseMembershipKind>,
+) -> Vec<Capability> {
+  let membership = match course_membership_kind {
+    Some(CourseMembershipKind::Instructor) => INSTRUCTOR,
+    Some(CourseMembershipKind::TeachingAssistant) => TEACHING_ASSISTANT,
+    Some(CourseMembershipKind::Student) => STUDENT,
+    Some(CourseMembershipKind::Observer) => OBSERVER,
+    Some(CourseMembershipKind::Cancel) | None => &[],
+  };
+
+  let mut capabilities = adminship_kind.map(school_capabilities).unwrap_or_default();
+  for capability in membership {
+    if !capabilities.contains(capability) {
+      capabilities.push(*capability);
+    }
+  }
+  capabilities
+}
+
+// whether a school key may grant this kind of adminship
+pub fn school_key_grantable(adminship_kind: &AdminshipKind) -> bool {
+  !matches!(adminship_kind, AdminshipKind::Cancel)
+}
+
+// whether a course key may grant this kind of membership
+pub fn course_key_grantable(course_membership_kind: &CourseMembershipKind) -> bool {
+  !matches!(course_membership_kind, CourseMembershipKind::Cancel)
+}
+
+// an empty list names nobody, so it is not a request for oneself
+fn only_self(user_id: i64, attendee_user_ids: &[i64]) -> bool {
+  !attendee_user_ids.is_empty() && attendee_user_ids.iter().all(|&x| x == user_id)
+}
+
+// the capability needed in the relevant school or course, None if anyone may
+// user_id is the user making the request
+// keys, subscriptions and new schools are checked separately
+pub fn required_capability(user_id: i64, props: &BatchNewItemProps) -> Option<Capability> {
+  match props {
+    BatchNewItemProps::SubscriptionNew(_) => None,
+    BatchNewItemProps::SchoolNew(_) => None,
+    BatchNewItemProps::SchoolDataNew(_) => Some(Capability::SchoolEdit),
+    BatchNewItemProps::SchoolDurationNew(_) => Some(Capability::SchoolDurationManage),
+    BatchNewItemProps::SchoolDurationDataNew(_) => Some(Capability::SchoolDurationManage),
+    BatchNewItemProps::SchoolKeyNew(_) => Some(Capability::SchoolKeyManage),
+    BatchNewItemProps::SchoolKeyDataNew(_) => Some(Capability::SchoolKeyManage),
+    BatchNewItemProps::AdminshipNewCancel(_) => Some(Capability::AdminshipManage),
+    BatchNewItemProps::AdminshipNewKey(_) => None,
+    BatchNewItemProps::LocationNew(_) => Some(Capability::LocationManage),
+    BatchNewItemProps::LocationDataNew(_) => Some(Capability::LocationManage),
+    BatchNewItemProps::CourseNew(_) => Some(Capability::CourseCreate),
+    BatchNewItemProps::CourseDataNew(_) => Some(Capability::CourseEdit),
+    BatchNewItemProps::CourseKeyNew(_) => Some(Capability::CourseKeyManage),
+    BatchNewItemProps::CourseKeyDataNew(_) => Some(Capability::CourseKeyManage),
+    // anyone may leave a course
+    BatchNewItemProps::CourseMembershipNewCancel(p) if p.user_id == user_id => None,
+    BatchNewItemProps::CourseMembershipNewCancel(_) => Some(Capability::CourseMembershipManage),
+    BatchNewItemProps::CourseMembershipNewKey(_) => None,
+    BatchNewItemProps::SessionNew(_) => Some(Capability::SessionManage),
+    BatchNewItemProps::SessionDataNew(_) => Some(Capability::SessionManage),
+    BatchNewItemProps::SessionRequestNew(_) => Some(Capability::SessionRequestCreate),
+    BatchNewItemProps::SessionRequestResponseNew(_) => Some(Capability::SessionRequestRespond),
+    BatchNewItemProps::CommitmentNew(p) if only_self(user_id, &p.attendee_user_ids) => {
+      Some(Capability::CommitmentCreateSelf)
+    }
+    BatchNewItemProps::CommitmentNew(_) => Some(Capability::CommitmentManage),
+    BatchNewItemProps::CommitmentNewSlot(_) => Some(Capability::CommitmentCreateSelf),
+    BatchNewItemProps::AvailabilityNew(_) => Some(Capability::AvailabilityManage),
+    BatchNewItemProps::AvailabilityDataNew(_) => Some(Capability::AvailabilityManage),
+    BatchNewItemProps::WaitlistEntryNew(p) if only_self(user_id, &p.attendee_user_ids) => {
+      Some(Capability::CommitmentCreateSelf)
+    }
+    BatchNewItemProps::WaitlistEntryNew(_) => Some(Capability::CommitmentManage),
+    BatchNewItemProps::EncounterNew(_) => Some(Capability::EncounterCreate),
+    BatchNewItemProps::EncounterNewAttends(_) => Some(Capability::EncounterCreate),
+    BatchNewItemProps::StayNew(_) => Some(Capability::StayManage),
+    BatchNewItemProps::StayDataNew(_) => Some(Capability::StayManage),
+    BatchNewItemProps::WebhookNew(_) => Some(Capability::WebhookManage),
+    BatchNewItemProps::WebhookDataNew(_) => Some(Capability::WebhookManage),
+    BatchNewItemProps::DeviceNew(_) => Some(Capability::DeviceManage),
+    BatchNewItemProps::DeviceDataNew(_) => Some(Capability::DeviceManage),
+  }
+}
+
+// whether a user with these capabilities (in the relevant school or course) may make the request
+pub fn permitted(capabilities: &[Capability], user_id: i64, props: &BatchNewItemProps) -> bool {
+  match required_capability(user_id, props) {
+    // whoever manages commitments may also commit themselves
+    Some(Capability::CommitmentCreateSelf) => {
+      capabilities.contains(&Capability::CommitmentCreateSelf)
+        || capabilities.contains(&Capability::CommitmentManage)
+    }
+    Some(capability) => capabilities.contains(&capability),
+    None => true,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::request::*;
+  use crate::secret::Secret;
+
+  const USER_ID: i64 = 1;
+  const OTHER_USER_ID: i64 = 2;
+
+  // every kind of New props, named, with the self and other forms where it matters
+  fn items() -> Vec<(&'static str, BatchNewItemProps)> {
+    let key = || Secret::from("key");
+    vec![
+      (
+        "SubscriptionNew",
+        BatchNewItemProps::SubscriptionNew(SubscriptionNewProps {
+          subscription_kind: SubscriptionKind::Valid,
+          subscription_plan: SubscriptionPlan::Basic,
+          auto_renew: false,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SchoolNew",
+        BatchNewItemProps::SchoolNew(SchoolNewProps {
+          name: String::new(),
+          description: String::new(),
+          whole: true,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SchoolDataNew",
+        BatchNewItemProps::SchoolDataNew(SchoolDataNewProps {
+          school_id: 1,
+          name: String::new(),
+          description: String::new(),
+          active: true,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SchoolDurationNew",
+        BatchNewItemProps::SchoolDurationNew(SchoolDurationNewProps {
+          school_id: 1,
+          day: 0,
+          minute_start: 0,
+          minute_end: 60,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SchoolDurationDataNew",
+        BatchNewItemProps::SchoolDurationDataNew(SchoolDurationDataNewProps {
+          school_duration_id: 1,
+          day: 0,
+          minute_start: 0,
+          minute_end: 60,
+          active: true,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SchoolKeyNew",
+        BatchNewItemProps::SchoolKeyNew(SchoolKeyNewProps {
+          school_id: 1,
+          adminship_kind: AdminshipKind::Admin,
+          start_time: 0,
+          end_time: 1,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SchoolKeyDataNew",
+        BatchNewItemProps::SchoolKeyDataNew(SchoolKeyDataNewProps {
+          school_key_key: key(),
+          active: false,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "AdminshipNewCancel",
+        BatchNewItemProps::AdminshipNewCancel(AdminshipNewCancelProps {
+          user_id: OTHER_USER_ID,
+          school_id: 1,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "AdminshipNewKey",
+        BatchNewItemProps::AdminshipNewKey(AdminshipNewKeyProps {
+          school_key_key: key(),
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "LocationNew",
+        BatchNewItemProps::LocationNew(LocationNewProps {
+          school_id: 1,
+          name: String::new(),
+          address: String::new(),
+          phone: String::new(),
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "LocationDataNew",
+        BatchNewItemProps::LocationDataNew(LocationDataNewProps {
+          location_id: 1,
+          name: String::new(),
+          address: String::new(),
+          phone: String::new(),
+          active: true,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "CourseNew",
+        BatchNewItemProps::CourseNew(CourseNewProps {
+          school_id: 1,
+          location_id: 1,
+          name: String::new(),
+          description: String::new(),
+          homeroom: false,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "CourseDataNew",
+        BatchNewItemProps::CourseDataNew(CourseDataNewProps {
+          course_id: 1,
+          location_id: 1,
+          name: String::new(),
+          description: String::new(),
+          homeroom: false,
+          active: true,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "CourseKeyNew",
+        BatchNewItemProps::CourseKeyNew(CourseKeyNewProps {
+          course_id: 1,
+          course_membership_kind: CourseMembershipKind::Student,
+          max_uses: 1,
+          start_time: 0,
+          end_time: 1,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "CourseKeyDataNew",
+        BatchNewItemProps::CourseKeyDataNew(CourseKeyDataNewProps {
+          course_key_key: key(),
+          active: false,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "CourseMembershipNewCancel(self)",
+        BatchNewItemProps::CourseMembershipNewCancel(CourseMembershipNewCancelProps {
+          user_id: USER_ID,
+          course_id: 1,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "CourseMembershipNewCancel(other)",
+        BatchNewItemProps::CourseMembershipNewCancel(CourseMembershipNewCancelProps {
+          user_id: OTHER_USER_ID,
+          course_id: 1,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "CourseMembershipNewKey",
+        BatchNewItemProps::CourseMembershipNewKey(CourseMembershipNewKeyProps {
+          course_key_key: key(),
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SessionNew",
+        BatchNewItemProps::SessionNew(SessionNewProps {
+          name: String::new(),
+          course_id: 1,
+          start_time: 0,
+          end_time: 1,
+          attendee_user_ids: vec![],
+          max_attendees: None,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SessionDataNew",
+        BatchNewItemProps::SessionDataNew(SessionDataNewProps {
+          session_id: 1,
+          name: String::new(),
+          active: true,
+          start_time: 0,
+          end_time: 1,
+          max_attendees: None,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SessionRequestNew",
+        BatchNewItemProps::SessionRequestNew(SessionRequestNewProps {
+          course_id: 1,
+          message: String::new(),
+          start_time: 0,
+          end_time: 1,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "SessionRequestResponseNew",
+        BatchNewItemProps::SessionRequestResponseNew(SessionRequestResponseNewProps {
+          session_request_id: 1,
+          message: String::new(),
+          session_id: None,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      ("CommitmentNew(self)", commitment_new(vec![USER_ID])),
+      (
+        "CommitmentNew(other)",
+        commitment_new(vec![USER_ID, OTHER_USER_ID]),
+      ),
+      ("CommitmentNew(nobody)", commitment_new(vec![])),
+      (
+        "CommitmentNewSlot",
+        BatchNewItemProps::CommitmentNewSlot(CommitmentNewSlotProps {
+          availability_id: 1,
+          start_time: 0,
+          name: String::new(),
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "AvailabilityNew",
+        BatchNewItemProps::AvailabilityNew(AvailabilityNewProps {
+          course_id: 1,
+          start_time: 0,
+          end_time: 1,
+          slot_duration: 1,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "AvailabilityDataNew",
+        BatchNewItemProps::AvailabilityDataNew(AvailabilityDataNewProps {
+          availability_id: 1,
+          start_time: 0,
+          end_time: 1,
+          slot_duration: 1,
+          active: true,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      ("WaitlistEntryNew(self)", waitlist_entry_new(vec![USER_ID])),
+      (
+        "WaitlistEntryNew(other)",
+        waitlist_entry_new(vec![OTHER_USER_ID]),
+      ),
+      ("WaitlistEntryNew(nobody)", waitlist_entry_new(vec![])),
+      (
+        "EncounterNew",
+        BatchNewItemProps::EncounterNew(EncounterNewProps {
+          attendee_user_id: OTHER_USER_ID,
+          location_id: 1,
+          occurrence_time: None,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "EncounterNewAttends",
+        BatchNewItemProps::EncounterNewAttends(EncounterNewAttendsProps {
+          attendee_user_id: OTHER_USER_ID,
+          location_id: 1,
+          occurrence_time: None,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "StayNew",
+        BatchNewItemProps::StayNew(StayNewProps {
+          attendee_user_id: OTHER_USER_ID,
+          location_id: 1,
+          fst_time: Some(0),
+          fst_encounter_id: None,
+          snd_time: None,
+          snd_encounter_id: None,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "StayDataNew",
+        BatchNewItemProps::StayDataNew(StayDataNewProps {
+          stay_id: 1,
+          fst_time: Some(0),
+          fst_encounter_id: None,
+          snd_time: Some(1),
+          snd_encounter_id: None,
+          active: true,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "WebhookNew",
+        BatchNewItemProps::WebhookNew(WebhookNewProps {
+          school_id: 1,
+          url: String::new(),
+          webhook_event_kinds: vec![WebhookEventKind::CommitmentNew],
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "WebhookDataNew",
+        BatchNewItemProps::WebhookDataNew(WebhookDataNewProps {
+          webhook_id: 1,
+          active: false,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "DeviceNew",
+        BatchNewItemProps::DeviceNew(DeviceNewProps {
+          school_id: 1,
+          name: String::new(),
+          location_ids: vec![1],
+          stay_view: false,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+      (
+        "DeviceDataNew",
+        BatchNewItemProps::DeviceDataNew(DeviceDataNewProps {
+          device_id: 1,
+          active: false,
+          idempotency_key: None,
+          api_key: key(),
+        }),
+      ),
+    ]
+  }
+
+  fn commitment_new(attendee_user_ids: Vec<i64>) -> BatchNewItemProps {
+    BatchNewItemProps::CommitmentNew(CommitmentNewProps {
+      attendee_user_ids,
+      session_id: 1,
+      idempotency_key: None,
+      api_key: Secret::from("key"),
+      active: true,
+    })
+  }
+
+  fn waitlist_entry_new(attendee_user_ids: Vec<i64>) -> BatchNewItemProps {
+    BatchNewItemProps::WaitlistEntryNew(WaitlistEntryNewProps {
+      attendee_user_ids,
+      session_id: 1,
+      active: true,
+      idempotency_key: None,
+      api_key: Secret::from("key"),
+    })
+  }
+
+  // anyone may do these, whatever their role
+  const ANYONE: &[&str] = &[
+    "SubscriptionNew",
+    "SchoolNew",
+    "AdminshipNewKey",
+    "CourseMembershipNewCancel(self)",
+    "CourseMembershipNewKey",
+  ];
+
+  const COMMITMENTS: &[&str] = &[
+    "CommitmentNew(self)",
+    "CommitmentNew(other)",
+    "CommitmentNew(nobody)",
+    "WaitlistEntryNew(self)",
+    "WaitlistEntryNew(other)",
+    "WaitlistEntryNew(nobody)",
+  ];
+
+  const SELF_COMMITMENTS: &[&str] = &[
+    "CommitmentNew(self)",
+    "CommitmentNewSlot",
+    "WaitlistEntryNew(self)",
+  ];
+
+  const ATTENDANCE: &[&str] = &[
+    "EncounterNew",
+    "EncounterNewAttends",
+    "StayNew",
+    "StayDataNew",
+  ];
+
+  const SESSIONS: &[&str] = &[
+    "SessionNew",
+    "SessionDataNew",
+    "SessionRequestResponseNew",
+    "CommitmentNewSlot",
+  ];
+
+  fn permitted_names(capabilities: &[Capability]) -> Vec<&'static str> {
+    items()
+      .into_iter()
+      .filter(|(_, props)| permitted(capabilities, USER_ID, props))
+      .map(|(name, _)| name)
+      .collect()
+  }
+
+  fn assert_permits(capabilities: Vec<Capability>, groups: &[&[&str]]) {
+    let expected: Vec<&str> = items()
+      .into_iter()
+      .map(|(name, _)| name)
+      .filter(|name| groups.iter().any(|group| group.contains(name)))
+      .collect();
+    assert_eq!(permitted_names(&capabilities), expected);
+  }
+
+  #[test]
+  fn admin_may_do_everything_but_request_sessions() {
+    let everything: Vec<&str> = items()
+      .into_iter()
+      .map(|(name, _)| name)
+      .filter(|&name| name != "SessionRequestNew")
+      .collect();
+    assert_eq!(
+      permitted_names(&course_capabilities(Some(&AdminshipKind::Admin), None)),
+      everything
+    );
+  }
+
+  #[test]
+  fn front_office_permits() {
+    assert_permits(
+      course_capabilities(Some(&AdminshipKind::FrontOffice), None),
+      &[
+        ANYONE,
+        &[
+          "LocationNew",
+          "LocationDataNew",
+          "DeviceNew",
+          "DeviceDataNew",
+        ],
+        ATTENDANCE,
+      ],
+    );
+  }
+
+  #[test]
+  fn counselor_and_cancelled_admin_permit_only_what_anyone_may() {
+    for adminship_kind in [AdminshipKind::Counselor, AdminshipKind::Cancel] {
+      assert_permits(course_capabilities(Some(&adminship_kind), None), &[ANYONE]);
+    }
+  }
+
+  #[test]
+  fn instructor_permits() {
+    assert_permits(
+      course_capabilities(None, Some(&CourseMembershipKind::Instructor)),
+      &[
+        ANYONE,
+        &[
+          "CourseDataNew",
+          "CourseKeyNew",
+          "CourseKeyDataNew",
+          "CourseMembershipNewCancel(other)",
+          "AvailabilityNew",
+          "AvailabilityDataNew",
+        ],
+        SESSIONS,
+        COMMITMENTS,
+        ATTENDANCE,
+      ],
+    );
+  }
+
+  #[test]
+  fn teaching_assistant_permits() {
+    assert_permits(
+      course_capabilities(None, Some(&CourseMembershipKind::TeachingAssistant)),
+      &[ANYONE, SESSIONS, COMMITMENTS, ATTENDANCE],
+    );
+  }
+
+  #[test]
+  fn student_permits() {
+    assert_permits(
+      course_capabilities(None, Some(&CourseMembershipKind::Student)),
+      &[ANYONE, &["SessionRequestNew"], SELF_COMMITMENTS],
+    );
+  }
+
+  #[test]
+  fn observer_and_cancelled_member_permit_only_what_anyone_may() {
+    for course_membership_kind in [CourseMembershipKind::Observer, CourseMembershipKind::Cancel] {
+      assert_permits(
+        course_capabilities(None, Some(&course_membership_kind)),
+        &[ANYONE],
+      );
+    }
+  }
+
+  #[test]
+  fn teaching_assistant_cannot_manage_keys_or_memberships() {
+    let capabilities = course_capabilities(None, Some(&CourseMembershipKind::TeachingAssistant));
+    let permitted = permitted_names(&capabilities);
+    assert!(!permitted.contains(&"CourseKeyNew"));
+    assert!(!permitted.contains(&"CourseMembershipNewCancel(other)"));
+  }
+
+  #[test]
+  fn observer_is_denied_every_new_props_needing_a_capability() {
+    let capabilities = course_capabilities(None, Some(&CourseMembershipKind::Observer));
+    for (name, props) in items() {
+      if required_capability(USER_ID, &props).is_some() {
+        assert!(!permitted(&capabilities, USER_ID, &props), "{}", name);
+      }
+    }
+  }
+
+  #[test]
+  fn front_office_cannot_make_school_keys_or_edit_the_school() {
+    let capabilities = school_capabilities(&AdminshipKind::FrontOffice);
+    let permitted = permitted_names(&capabilities);
+    assert!(!permitted.contains(&"SchoolKeyNew"));
+    assert!(!permitted.contains(&"SchoolDataNew"));
+  }
+
+  #[test]
+  fn counselor_views_attendance_in_every_course() {
+    let course_membership_kinds = [
+      None,
+      Some(CourseMembershipKind::Student),
+      Some(CourseMembershipKind::Instructor),
+      Some(CourseMembershipKind::Cancel),
+      Some(CourseMembershipKind::TeachingAssistant),
+      Some(CourseMembershipKind::Observer),
+    ];
+    for course_membership_kind in &course_membership_kinds {
+      let capabilities = course_capabilities(
+        Some(&AdminshipKind::Counselor),
+        course_membership_kind.as_ref(),
+      );
+      assert!(capabilities.contains(&Capability::AttendanceView));
+    }
+  }
+
+  #[test]
+  fn commitments_for_others_or_nobody_need_commitment_manage() {
+    let other = commitment_new(vec![OTHER_USER_ID]);
+    let nobody = commitment_new(vec![]);
+    let own = commitment_new(vec![USER_ID, USER_ID]);
+    assert_eq!(
+      required_capability(USER_ID, &own),
+      Some(Capability::CommitmentCreateSelf)
+    );
+    assert_eq!(
+      required_capability(USER_ID, &other),
+      Some(Capability::CommitmentManage)
+    );
+    assert_eq!(
+      required_capability(USER_ID, &nobody),
+      Some(Capability::CommitmentManage)
+    );
+  }
+}
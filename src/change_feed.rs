@@ -0,0 +1,57 @@
+// Incremental mirroring of every record visible to an api key
+use super::client::InnexgoHoursService;
+use super::request::ChangeViewProps;
+use super::response::{Change, InnexgoHoursError};
+use super::secret::Secret;
+
+#[derive(Clone)]
+pub struct ChangeFeed {
+  service: InnexgoHoursService,
+  api_key: Secret,
+  count: Option<i64>,
+  watermark: i64,
+  more: bool,
+}
+
+impl ChangeFeed {
+  // starts after watermark (a sequence number, see ChangeViewProps), pass 0 to mirror everything
+  pub fn new(service: InnexgoHoursService, api_key: Secret, watermark: i64) -> Self {
+    ChangeFeed {
+      service,
+      api_key,
+      count: None,
+      watermark,
+      more: true,
+    }
+  }
+
+  pub fn with_count(mut self, count: i64) -> Self {
+    self.count = Some(count);
+    self
+  }
+
+  // persist this to resume after a restart
+  pub fn watermark(&self) -> i64 {
+    self.watermark
+  }
+
+  // false once the last page returned everything available at the time
+  pub fn more(&self) -> bool {
+    self.more
+  }
+
+  // fetches the next page and advances the watermark past it
+  pub async fn next(&mut self) -> Result<Vec<Change>, InnexgoHoursError> {
+    let page = self
+      .service
+      .change_view(ChangeViewProps {
+        min_sequence: self.watermark,
+        count: self.count,
+        api_key: self.api_key.clone(),
+      })
+      .await?;
+    self.watermark = page.watermark;
+    self.more = page.more;
+    Ok(page.changes)
+  }
+}
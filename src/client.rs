@@ -1,16 +1,634 @@
+use super::booking;
+use super::cache::{CacheConfig, CacheMetrics, CachedEntity, ResponseCache};
+use super::event_stream;
+use super::key;
+use super::request;
+use super::response;
+use super::response::InnexgoHoursError;
+use super::secret::Secret;
+use either::Either;
+use futures_util::stream::Stream;
 use reqwest::Client;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+// how many times a create request is resent after a network failure
+const NEW_RETRIES: u32 = 3;
+
+// a request that takes longer than this fails with Network (and so is retried if it is a create)
+// event streams are long lived, so only their connection is bounded
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+// one key per logical call, so that retries are recognized by the server
+fn ensure_idempotency_key(idempotency_key: &mut Option<String>) {
+  idempotency_key.get_or_insert_with(|| Uuid::new_v4().to_string());
+}
 
 #[derive(Clone)]
 pub struct InnexgoHoursService {
   client: Client,
   innexgo_hours_service_url: String,
+  cache: Option<Arc<ResponseCache>>,
+  // sent as a bearer token with every request
+  api_key: Option<Secret>,
 }
 
 impl InnexgoHoursService {
   pub async fn new(innexgo_hours_service_url: &str) -> Self {
     InnexgoHoursService {
       innexgo_hours_service_url: String::from(innexgo_hours_service_url),
-      client: Client::new(),
+      client: Client::builder()
+        .connect_timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("failed to build client"),
+      cache: None,
+      api_key: None,
+    }
+  }
+
+  // authenticates every request with this key (or device key), so props may leave api_key empty
+  // a non empty api_key in the props is still sent, and is used by the server instead
+  pub fn with_api_key(mut self, api_key: Secret) -> Self {
+    self.api_key = Some(api_key);
+    self
+  }
+
+  fn post(&self, endpoint: &str) -> reqwest::RequestBuilder {
+    let req = self
+      .client
+      .post(format!("{}/{}", self.innexgo_hours_service_url, endpoint));
+    match self.api_key {
+      Some(ref api_key) => req.bearer_auth(api_key.expose()),
+      None => req,
+    }
+  }
+
+  // caches views of schools, locations and courses (and their data) in memory
+  // the cache is shared by clones, including those given a different key with with_api_key
+  pub fn with_cache(mut self, config: CacheConfig) -> Self {
+    self.cache = Some(Arc::new(ResponseCache::new(config)));
+    self
+  }
+
+  pub fn cache_metrics(&self) -> Option<CacheMetrics> {
+    self.cache.as_ref().map(|cache| cache.metrics())
+  }
+
+  async fn query<Props: Serialize, T: DeserializeOwned>(
+    &self,
+    endpoint: &str,
+    props: Props,
+  ) -> Result<T, InnexgoHoursError> {
+    let resp = self
+      .post(endpoint)
+      .timeout(REQUEST_TIMEOUT)
+      .json(&props)
+      .send()
+      .await
+      .map_err(|_| InnexgoHoursError::Network)?;
+
+    // the timeout also covers reading the body
+    let decode_error = |e: reqwest::Error| {
+      if e.is_timeout() {
+        InnexgoHoursError::Network
+      } else {
+        InnexgoHoursError::DecodeError
+      }
+    };
+
+    if resp.status().is_success() {
+      resp.json().await.map_err(decode_error)
+    } else {
+      Err(resp.json().await.map_err(decode_error)?)
+    }
+  }
+
+  // opens a server-sent event stream
+  async fn subscribe<Props: Serialize, T: DeserializeOwned>(
+    &self,
+    endpoint: &str,
+    props: Props,
+  ) -> Result<impl Stream<Item = Result<T, InnexgoHoursError>>, InnexgoHoursError> {
+    let resp = self
+      .post(endpoint)
+      .header(reqwest::header::ACCEPT, "text/event-stream")
+      .json(&props)
+      .send()
+      .await
+      .map_err(|_| InnexgoHoursError::Network)?;
+
+    if resp.status().is_success() {
+      Ok(event_stream::decode(resp))
+    } else {
+      Err(
+        resp
+          .json()
+          .await
+          .map_err(|_| InnexgoHoursError::DecodeError)?,
+      )
+    }
+  }
+
+  // resends on network failure, relying on the idempotency key to avoid duplicates
+  async fn query_new<Props: Serialize, T: DeserializeOwned>(
+    &self,
+    endpoint: &str,
+    props: Props,
+  ) -> Result<T, InnexgoHoursError> {
+    let mut tries = 0;
+    loop {
+      match self.query(endpoint, &props).await {
+        Err(InnexgoHoursError::Network) if tries < NEW_RETRIES => {
+          tries += 1;
+          tokio::time::sleep(Duration::from_millis(100 << tries)).await;
+        }
+        x => return x,
+      }
+    }
+  }
+
+  async fn query_cached<Props: Serialize, T: Serialize + DeserializeOwned>(
+    &self,
+    entity: CachedEntity,
+    endpoint: &str,
+    ids: Option<Vec<i64>>,
+    props: Props,
+  ) -> Result<T, InnexgoHoursError> {
+    let cache = match self.cache {
+      Some(ref cache) => cache,
+      None => return self.query(endpoint, props).await,
+    };
+
+    let key = ResponseCache::key(endpoint, self.api_key.as_ref(), &props);
+    if let Some(value) = key.as_ref().and_then(|key| cache.get(entity, key)) {
+      return Ok(value);
+    }
+
+    let value = self.query(endpoint, props).await?;
+    if let Some(key) = key {
+      cache.insert(entity, key, ids, &value);
+    }
+    Ok(value)
+  }
+
+  fn invalidate(&self, entity: CachedEntity, id: i64) {
+    if let Some(ref cache) = self.cache {
+      cache.invalidate(entity, id);
     }
   }
+
+  pub async fn subscription_new(
+    &self,
+    mut props: request::SubscriptionNewProps,
+  ) -> Result<response::Subscription, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/subscription/new", props).await
+  }
+
+  pub async fn subscription_view(
+    &self,
+    props: request::SubscriptionViewProps,
+  ) -> Result<Vec<response::Subscription>, InnexgoHoursError> {
+    self.query("public/subscription/view", props).await
+  }
+
+  pub async fn subscription_usage_view(
+    &self,
+    props: request::SubscriptionUsageViewProps,
+  ) -> Result<response::SubscriptionUsage, InnexgoHoursError> {
+    self.query("public/subscription_usage/view", props).await
+  }
+
+  pub async fn school_new(
+    &self,
+    mut props: request::SchoolNewProps,
+  ) -> Result<response::SchoolData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    let school_data: response::SchoolData = self.query_new("public/school/new", props).await?;
+    self.invalidate(CachedEntity::School, school_data.school.school_id);
+    Ok(school_data)
+  }
+
+  pub async fn school_data_new(
+    &self,
+    mut props: request::SchoolDataNewProps,
+  ) -> Result<response::SchoolData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    let school_id = props.school_id;
+    let school_data = self.query_new("public/school_data/new", props).await?;
+    self.invalidate(CachedEntity::School, school_id);
+    Ok(school_data)
+  }
+
+  pub async fn school_view(
+    &self,
+    props: request::SchoolViewProps,
+  ) -> Result<Vec<response::School>, InnexgoHoursError> {
+    let ids = props.school_id.clone();
+    self
+      .query_cached(CachedEntity::School, "public/school/view", ids, props)
+      .await
+  }
+
+  pub async fn school_data_view(
+    &self,
+    props: request::SchoolDataViewProps,
+  ) -> Result<Vec<response::SchoolData>, InnexgoHoursError> {
+    let ids = props.school_id.clone();
+    self
+      .query_cached(CachedEntity::School, "public/school_data/view", ids, props)
+      .await
+  }
+
+  pub async fn school_key_preview(
+    &self,
+    mut props: request::SchoolKeyPreviewProps,
+  ) -> Result<response::SchoolKeyPreview, InnexgoHoursError> {
+    props.school_key_key = key::prepare(&props.school_key_key)?;
+    self.query("public/school_key/preview", props).await
+  }
+
+  pub async fn course_key_preview(
+    &self,
+    mut props: request::CourseKeyPreviewProps,
+  ) -> Result<response::CourseKeyPreview, InnexgoHoursError> {
+    props.course_key_key = key::prepare(&props.course_key_key)?;
+    self.query("public/course_key/preview", props).await
+  }
+
+  // the key is checked locally first, so typos fail fast with KeyMalformed, see key::prepare
+  pub async fn adminship_new_key(
+    &self,
+    mut props: request::AdminshipNewKeyProps,
+  ) -> Result<response::Adminship, InnexgoHoursError> {
+    props.school_key_key = key::prepare(&props.school_key_key)?;
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/adminship/new_key", props).await
+  }
+
+  // the key is checked locally first, so typos fail fast with KeyMalformed, see key::prepare
+  pub async fn course_membership_new_key(
+    &self,
+    mut props: request::CourseMembershipNewKeyProps,
+  ) -> Result<response::CourseMembership, InnexgoHoursError> {
+    props.course_key_key = key::prepare(&props.course_key_key)?;
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self
+      .query_new("public/course_membership/new_key", props)
+      .await
+  }
+
+  pub async fn location_new(
+    &self,
+    mut props: request::LocationNewProps,
+  ) -> Result<response::LocationData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    let location_data: response::LocationData =
+      self.query_new("public/location/new", props).await?;
+    self.invalidate(CachedEntity::Location, location_data.location.location_id);
+    Ok(location_data)
+  }
+
+  pub async fn location_data_new(
+    &self,
+    mut props: request::LocationDataNewProps,
+  ) -> Result<response::LocationData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    let location_id = props.location_id;
+    let location_data = self.query_new("public/location_data/new", props).await?;
+    self.invalidate(CachedEntity::Location, location_id);
+    Ok(location_data)
+  }
+
+  pub async fn location_view(
+    &self,
+    props: request::LocationViewProps,
+  ) -> Result<Vec<response::Location>, InnexgoHoursError> {
+    let ids = props.location_id.clone();
+    self
+      .query_cached(CachedEntity::Location, "public/location/view", ids, props)
+      .await
+  }
+
+  pub async fn location_data_view(
+    &self,
+    props: request::LocationDataViewProps,
+  ) -> Result<Vec<response::LocationData>, InnexgoHoursError> {
+    let ids = props.location_id.clone();
+    self
+      .query_cached(
+        CachedEntity::Location,
+        "public/location_data/view",
+        ids,
+        props,
+      )
+      .await
+  }
+
+  pub async fn device_new(
+    &self,
+    mut props: request::DeviceNewProps,
+  ) -> Result<response::DeviceCredential, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/device/new", props).await
+  }
+
+  pub async fn device_data_new(
+    &self,
+    mut props: request::DeviceDataNewProps,
+  ) -> Result<response::DeviceData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/device_data/new", props).await
+  }
+
+  pub async fn device_view(
+    &self,
+    props: request::DeviceViewProps,
+  ) -> Result<Vec<response::Device>, InnexgoHoursError> {
+    self.query("public/device/view", props).await
+  }
+
+  pub async fn device_data_view(
+    &self,
+    props: request::DeviceDataViewProps,
+  ) -> Result<Vec<response::DeviceData>, InnexgoHoursError> {
+    self.query("public/device_data/view", props).await
+  }
+
+  pub async fn course_new(
+    &self,
+    mut props: request::CourseNewProps,
+  ) -> Result<response::CourseData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    let course_data: response::CourseData = self.query_new("public/course/new", props).await?;
+    self.invalidate(CachedEntity::Course, course_data.course.course_id);
+    Ok(course_data)
+  }
+
+  pub async fn course_data_new(
+    &self,
+    mut props: request::CourseDataNewProps,
+  ) -> Result<response::CourseData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    let course_id = props.course_id;
+    let course_data = self.query_new("public/course_data/new", props).await?;
+    self.invalidate(CachedEntity::Course, course_id);
+    Ok(course_data)
+  }
+
+  pub async fn course_view(
+    &self,
+    props: request::CourseViewProps,
+  ) -> Result<Vec<response::Course>, InnexgoHoursError> {
+    let ids = props.course_id.clone();
+    self
+      .query_cached(CachedEntity::Course, "public/course/view", ids, props)
+      .await
+  }
+
+  pub async fn course_data_view(
+    &self,
+    props: request::CourseDataViewProps,
+  ) -> Result<Vec<response::CourseData>, InnexgoHoursError> {
+    let ids = props.course_id.clone();
+    self
+      .query_cached(CachedEntity::Course, "public/course_data/view", ids, props)
+      .await
+  }
+
+  pub async fn capabilities_view(
+    &self,
+    props: request::CapabilitiesViewProps,
+  ) -> Result<response::Capabilities, InnexgoHoursError> {
+    self.query("public/capabilities/view", props).await
+  }
+
+  pub async fn change_view(
+    &self,
+    props: request::ChangeViewProps,
+  ) -> Result<response::ChangePage, InnexgoHoursError> {
+    self.query("public/change/view", props).await
+  }
+
+  pub async fn encounter_subscribe(
+    &self,
+    props: request::EncounterSubscribeProps,
+  ) -> Result<
+    impl Stream<Item = Result<response::EncounterEvent, InnexgoHoursError>>,
+    InnexgoHoursError,
+  > {
+    self.subscribe("public/encounter/subscribe", props).await
+  }
+
+  pub async fn session_request_subscribe(
+    &self,
+    props: request::SessionRequestSubscribeProps,
+  ) -> Result<
+    impl Stream<Item = Result<response::SessionRequestEvent, InnexgoHoursError>>,
+    InnexgoHoursError,
+  > {
+    self
+      .subscribe("public/session_request/subscribe", props)
+      .await
+  }
+
+  pub async fn webhook_new(
+    &self,
+    mut props: request::WebhookNewProps,
+  ) -> Result<response::WebhookCredential, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/webhook/new", props).await
+  }
+
+  pub async fn webhook_data_new(
+    &self,
+    mut props: request::WebhookDataNewProps,
+  ) -> Result<response::WebhookData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/webhook_data/new", props).await
+  }
+
+  pub async fn webhook_view(
+    &self,
+    props: request::WebhookViewProps,
+  ) -> Result<Vec<response::Webhook>, InnexgoHoursError> {
+    self.query("public/webhook/view", props).await
+  }
+
+  pub async fn webhook_data_view(
+    &self,
+    props: request::WebhookDataViewProps,
+  ) -> Result<Vec<response::WebhookData>, InnexgoHoursError> {
+    self.query("public/webhook_data/view", props).await
+  }
+
+  pub async fn session_new(
+    &self,
+    mut props: request::SessionNewProps,
+  ) -> Result<response::SessionData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/session/new", props).await
+  }
+
+  pub async fn session_data_view(
+    &self,
+    props: request::SessionDataViewProps,
+  ) -> Result<Vec<response::SessionData>, InnexgoHoursError> {
+    self.query("public/session_data/view", props).await
+  }
+
+  pub async fn commitment_view(
+    &self,
+    props: request::CommitmentViewProps,
+  ) -> Result<Vec<response::Commitment>, InnexgoHoursError> {
+    self.query("public/commitment/view", props).await
+  }
+
+  pub async fn availability_new(
+    &self,
+    mut props: request::AvailabilityNewProps,
+  ) -> Result<response::AvailabilityData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/availability/new", props).await
+  }
+
+  pub async fn availability_data_new(
+    &self,
+    mut props: request::AvailabilityDataNewProps,
+  ) -> Result<response::AvailabilityData, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/availability_data/new", props).await
+  }
+
+  pub async fn availability_view(
+    &self,
+    props: request::AvailabilityViewProps,
+  ) -> Result<Vec<response::Availability>, InnexgoHoursError> {
+    self.query("public/availability/view", props).await
+  }
+
+  pub async fn availability_data_view(
+    &self,
+    props: request::AvailabilityDataViewProps,
+  ) -> Result<Vec<response::AvailabilityData>, InnexgoHoursError> {
+    self.query("public/availability_data/view", props).await
+  }
+
+  pub async fn availability_slot_view(
+    &self,
+    props: request::AvailabilitySlotViewProps,
+  ) -> Result<Vec<response::AvailabilitySlot>, InnexgoHoursError> {
+    self.query("public/availability_slot/view", props).await
+  }
+
+  pub async fn free_window_view(
+    &self,
+    props: request::FreeWindowViewProps,
+  ) -> Result<Vec<response::FreeWindow>, InnexgoHoursError> {
+    self.query("public/free_window/view", props).await
+  }
+
+  pub async fn commitment_new_slot(
+    &self,
+    mut props: request::CommitmentNewSlotProps,
+  ) -> Result<response::Commitment, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/commitment/new_slot", props).await
+  }
+
+  // claims the earliest free slot within the requested window
+  // only if none fit (or all are claimed first by someone else) is the session request filed
+  // name is the name of the session created when a slot is claimed
+  pub async fn session_book(
+    &self,
+    name: &str,
+    props: request::SessionRequestNewProps,
+  ) -> Result<Either<response::Commitment, response::SessionRequest>, InnexgoHoursError> {
+    let slots = self
+      .availability_slot_view(request::AvailabilitySlotViewProps {
+        availability_id: None,
+        creator_user_id: None,
+        course_id: Some(vec![props.course_id]),
+        min_start_time: Some(props.start_time),
+        max_end_time: Some(props.end_time),
+        api_key: props.api_key.clone(),
+      })
+      .await?;
+
+    let slots = booking::fitting_slots(&slots, props.start_time, props.end_time);
+    let commitment = booking::claim_first_slot(slots, |slot| {
+      self.commitment_new_slot(request::CommitmentNewSlotProps {
+        availability_id: slot.availability.availability_id,
+        start_time: slot.start_time,
+        name: String::from(name),
+        idempotency_key: None,
+        api_key: props.api_key.clone(),
+      })
+    })
+    .await?;
+
+    if let Some(commitment) = commitment {
+      return Ok(Either::Left(commitment));
+    }
+    self.session_request_new(props).await.map(Either::Right)
+  }
+
+  pub async fn session_request_new(
+    &self,
+    mut props: request::SessionRequestNewProps,
+  ) -> Result<response::SessionRequest, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/session_request/new", props).await
+  }
+
+  pub async fn commitment_new(
+    &self,
+    mut props: request::CommitmentNewProps,
+  ) -> Result<Vec<response::Commitment>, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/commitment/new", props).await
+  }
+
+  pub async fn waitlist_entry_new(
+    &self,
+    mut props: request::WaitlistEntryNewProps,
+  ) -> Result<Vec<response::WaitlistEntry>, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/waitlist_entry/new", props).await
+  }
+
+  pub async fn waitlist_entry_view(
+    &self,
+    props: request::WaitlistEntryViewProps,
+  ) -> Result<Vec<response::WaitlistEntry>, InnexgoHoursError> {
+    self.query("public/waitlist_entry/view", props).await
+  }
+
+  pub async fn encounter_new(
+    &self,
+    mut props: request::EncounterNewProps,
+  ) -> Result<response::Encounter, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/encounter/new", props).await
+  }
+
+  // returns one result per item, in the same order as props.encounters
+  pub async fn encounter_bulk_new(
+    &self,
+    mut props: request::EncounterBulkNewProps,
+  ) -> Result<Vec<response::EncounterBulkNewItemResponse>, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/encounter/bulk_new", props).await
+  }
+
+  // all items succeed together, or the first error is returned and nothing is created
+  pub async fn batch_new(
+    &self,
+    mut props: request::BatchNewProps,
+  ) -> Result<Vec<response::BatchNewItemResponse>, InnexgoHoursError> {
+    ensure_idempotency_key(&mut props.idempotency_key);
+    self.query_new("public/batch/new", props).await
+  }
 }
@@ -0,0 +1,551 @@
+// Detecting scheduling conflicts for proposed sessions, commitments and session requests
+use super::request::{
+  CommitmentNewProps, CourseMembershipKind, SessionNewProps, SessionRequestNewProps,
+};
+use super::response::{
+  Commitment, CourseData, CourseMembership, InnexgoHoursError, SchoolDurationData, SessionData,
+};
+
+const MINUTE: i64 = 60 * 1000;
+const DAY: i64 = 24 * 60 * MINUTE;
+
+#[derive(Clone, Debug)]
+pub enum Conflict {
+  // the attendee is already committed to an overlapping session
+  AttendeeCommitment {
+    attendee_user_id: i64,
+    session_data: SessionData,
+  },
+  // an instructor (or teaching assistant) of the course runs an overlapping session
+  InstructorSession {
+    instructor_user_id: i64,
+    session_data: SessionData,
+  },
+  // an overlapping session is held at the same location
+  LocationSession {
+    location_id: i64,
+    session_data: SessionData,
+  },
+  // the school is not open for the whole of the session
+  SchoolClosed,
+}
+
+impl Conflict {
+  pub fn error(&self) -> InnexgoHoursError {
+    match self {
+      Conflict::AttendeeCommitment { .. } => InnexgoHoursError::ConflictAttendeeCommitment,
+      Conflict::InstructorSession { .. } => InnexgoHoursError::ConflictInstructorSession,
+      Conflict::LocationSession { .. } => InnexgoHoursError::ConflictLocationSession,
+      Conflict::SchoolClosed => InnexgoHoursError::ConflictSchoolClosed,
+    }
+  }
+}
+
+// everything conflicts are checked against
+// each slice should contain the most recent, active records (only_recent)
+#[derive(Clone, Copy, Debug)]
+pub struct ConflictContext<'a> {
+  pub session_datas: &'a [SessionData],
+  pub commitments: &'a [Commitment],
+  pub course_datas: &'a [CourseData],
+  pub course_memberships: &'a [CourseMembership],
+  // the open hours of the school, see school_open
+  pub school_duration_datas: &'a [SchoolDurationData],
+  // added to timestamps to get the school's local time
+  pub utc_offset: i64,
+}
+
+// half open intervals, so back to back sessions don't overlap
+pub fn overlaps(a_start: i64, a_end: i64, b_start: i64, b_end: i64) -> bool {
+  a_start < b_end && b_start < a_end
+}
+
+// whether a single one of the school's open durations covers the whole interval
+// if there are none, the school is considered always open
+// day is the day of the week (0 is sunday), minutes are from local midnight
+pub fn school_open(
+  school_duration_datas: &[SchoolDurationData],
+  utc_offset: i64,
+  start_time: i64,
+  end_time: i64,
+) -> bool {
+  if school_duration_datas.is_empty() {
+    return true;
+  }
+
+  let local_start = start_time + utc_offset;
+  let local_end = end_time + utc_offset;
+  let day_start = local_start.div_euclid(DAY) * DAY;
+  // the unix epoch was a thursday
+  let day = (local_start.div_euclid(DAY) + 4).rem_euclid(7);
+
+  school_duration_datas.iter().any(|sdd| {
+    sdd.active
+      && sdd.day == day
+      && local_start >= day_start + sdd.minute_start * MINUTE
+      && local_end <= day_start + sdd.minute_end * MINUTE
+  })
+}
+
+// the users running the course's sessions: its instructors and teaching assistants
+// course_memberships should be the most recent record of each (only_recent)
+pub fn instructor_user_ids(course_memberships: &[CourseMembership], course_id: i64) -> Vec<i64> {
+  course_memberships
+    .iter()
+    .filter(|cm| cm.course.course_id == course_id)
+    .filter(|cm| {
+      matches!(
+        cm.course_membership_kind,
+        CourseMembershipKind::Instructor | CourseMembershipKind::TeachingAssistant
+      )
+    })
+    .map(|cm| cm.user_id)
+    .collect()
+}
+
+impl<'a> ConflictContext<'a> {
+  fn session_data(&self, session_id: i64) -> Option<&'a SessionData> {
+    self
+      .session_datas
+      .iter()
+      .find(|sd| sd.active && sd.session.session_id == session_id)
+  }
+
+  fn location_id(&self, course_id: i64) -> Option<i64> {
+    self
+      .course_datas
+      .iter()
+      .find(|cd| cd.course.course_id == course_id)
+      .map(|cd| cd.location.location_id)
+  }
+
+  fn instructor_user_ids(&self, course_id: i64) -> Vec<i64> {
+    instructor_user_ids(self.course_memberships, course_id)
+  }
+
+  fn overlapping(
+    &self,
+    session_id: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+  ) -> impl Iterator<Item = &'a SessionData> {
+    self.session_datas.iter().filter(move |sd| {
+      sd.active
+        && Some(sd.session.session_id) != session_id
+        && overlaps(sd.start_time, sd.end_time, start_time, end_time)
+    })
+  }
+
+  fn attendee_conflicts(
+    &self,
+    attendee_user_ids: &[i64],
+    session_id: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+  ) -> Vec<Conflict> {
+    let mut conflicts = vec![];
+    for session_data in self.overlapping(session_id, start_time, end_time) {
+      for commitment in self.commitments.iter().filter(|c| {
+        c.active
+          && c.session.session_id == session_data.session.session_id
+          && attendee_user_ids.contains(&c.attendee_user_id)
+      }) {
+        conflicts.push(Conflict::AttendeeCommitment {
+          attendee_user_id: commitment.attendee_user_id,
+          session_data: session_data.clone(),
+        });
+      }
+    }
+    conflicts
+  }
+
+  pub fn session_conflicts(&self, props: &SessionNewProps) -> Vec<Conflict> {
+    let mut conflicts = self.attendee_conflicts(
+      &props.attendee_user_ids,
+      None,
+      props.start_time,
+      props.end_time,
+    );
+
+    for instructor_user_id in self.instructor_user_ids(props.course_id) {
+      for session_data in self.overlapping(None, props.start_time, props.end_time) {
+        let course_id = session_data.session.course.course_id;
+        if self
+          .instructor_user_ids(course_id)
+          .contains(&instructor_user_id)
+        {
+          conflicts.push(Conflict::InstructorSession {
+            instructor_user_id,
+            session_data: session_data.clone(),
+          });
+        }
+      }
+    }
+
+    if let Some(location_id) = self.location_id(props.course_id) {
+      for session_data in self.overlapping(None, props.start_time, props.end_time) {
+        if self.location_id(session_data.session.course.course_id) == Some(location_id) {
+          conflicts.push(Conflict::LocationSession {
+            location_id,
+            session_data: session_data.clone(),
+          });
+        }
+      }
+    }
+
+    if !school_open(
+      self.school_duration_datas,
+      self.utc_offset,
+      props.start_time,
+      props.end_time,
+    ) {
+      conflicts.push(Conflict::SchoolClosed);
+    }
+
+    conflicts
+  }
+
+  // the requester is the user owning the api key the session request is sent with
+  // instructors are not checked, as the session request doesn't name one
+  pub fn session_request_conflicts(
+    &self,
+    requester_user_id: i64,
+    props: &SessionRequestNewProps,
+  ) -> Vec<Conflict> {
+    let mut conflicts =
+      self.attendee_conflicts(&[requester_user_id], None, props.start_time, props.end_time);
+
+    if !school_open(
+      self.school_duration_datas,
+      self.utc_offset,
+      props.start_time,
+      props.end_time,
+    ) {
+      conflicts.push(Conflict::SchoolClosed);
+    }
+
+    conflicts
+  }
+
+  // the session must be present in session_datas, otherwise nothing can conflict
+  pub fn commitment_conflicts(&self, props: &CommitmentNewProps) -> Vec<Conflict> {
+    match (props.active, self.session_data(props.session_id)) {
+      (true, Some(session_data)) => self.attendee_conflicts(
+        &props.attendee_user_ids,
+        Some(props.session_id),
+        session_data.start_time,
+        session_data.end_time,
+      ),
+      _ => vec![],
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fixture::{
+    commitment, course_data, course_membership, school_duration_data, session_data,
+  };
+  use crate::secret::Secret;
+
+  const HOUR: i64 = 60 * MINUTE;
+  // 2024-01-01T00:00:00Z, a monday
+  const MONDAY: i64 = 1_704_067_200_000;
+
+  fn session_new(course_id: i64, start_time: i64, end_time: i64) -> SessionNewProps {
+    SessionNewProps {
+      name: String::from("New"),
+      course_id,
+      start_time,
+      end_time,
+      attendee_user_ids: vec![],
+      max_attendees: None,
+      idempotency_key: None,
+      api_key: Secret::default(),
+    }
+  }
+
+  fn context<'a>(
+    session_datas: &'a [SessionData],
+    commitments: &'a [Commitment],
+    course_datas: &'a [CourseData],
+    course_memberships: &'a [CourseMembership],
+    school_duration_datas: &'a [SchoolDurationData],
+  ) -> ConflictContext<'a> {
+    ConflictContext {
+      session_datas,
+      commitments,
+      course_datas,
+      course_memberships,
+      school_duration_datas,
+      utc_offset: 0,
+    }
+  }
+
+  #[test]
+  fn overlaps_is_half_open() {
+    assert!(overlaps(0, 10, 5, 15));
+    assert!(overlaps(5, 15, 0, 10));
+    assert!(overlaps(0, 10, 2, 8));
+    assert!(overlaps(2, 8, 0, 10));
+    assert!(!overlaps(0, 10, 10, 20));
+    assert!(!overlaps(10, 20, 0, 10));
+    assert!(!overlaps(0, 10, 11, 20));
+  }
+
+  #[test]
+  fn school_open_weekday() {
+    // mondays, 09:00 to 17:00
+    let sdds = [school_duration_data(1, 9 * 60, 17 * 60)];
+    assert!(school_open(&sdds, 0, MONDAY + 9 * HOUR, MONDAY + 17 * HOUR));
+    assert!(!school_open(
+      &sdds,
+      0,
+      MONDAY + 8 * HOUR,
+      MONDAY + 10 * HOUR
+    ));
+    assert!(!school_open(
+      &sdds,
+      0,
+      MONDAY + 16 * HOUR,
+      MONDAY + 18 * HOUR
+    ));
+    // the same hours on sunday and tuesday
+    assert!(!school_open(
+      &sdds,
+      0,
+      MONDAY - 14 * HOUR,
+      MONDAY - 13 * HOUR
+    ));
+    assert!(!school_open(
+      &sdds,
+      0,
+      MONDAY + 34 * HOUR,
+      MONDAY + 35 * HOUR
+    ));
+    // a week later
+    assert!(school_open(
+      &sdds,
+      0,
+      MONDAY + 7 * 24 * HOUR + 10 * HOUR,
+      MONDAY + 7 * 24 * HOUR + 11 * HOUR
+    ));
+  }
+
+  #[test]
+  fn school_open_utc_offset() {
+    let sdds = [school_duration_data(1, 9 * 60, 17 * 60)];
+    // 23:00 sunday utc is 09:00 monday at utc+10
+    let start = MONDAY - HOUR;
+    assert!(!school_open(&sdds, 0, start, start + HOUR));
+    assert!(school_open(&sdds, 10 * HOUR, start, start + HOUR));
+    // 18:00 monday utc is 10:00 monday at utc-8
+    let start = MONDAY + 18 * HOUR;
+    assert!(!school_open(&sdds, 0, start, start + HOUR));
+    assert!(school_open(&sdds, -8 * HOUR, start, start + HOUR));
+  }
+
+  #[test]
+  fn school_open_without_durations() {
+    assert!(school_open(&[], 0, MONDAY, MONDAY + HOUR));
+    let mut sdd = school_duration_data(1, 0, 24 * 60);
+    sdd.active = false;
+    assert!(!school_open(&[sdd], 0, MONDAY, MONDAY + HOUR));
+  }
+
+  #[test]
+  fn school_open_needs_a_single_covering_duration() {
+    let sdds = [
+      school_duration_data(1, 9 * 60, 12 * 60),
+      school_duration_data(1, 13 * 60, 17 * 60),
+    ];
+    assert!(school_open(
+      &sdds,
+      0,
+      MONDAY + 13 * HOUR,
+      MONDAY + 14 * HOUR
+    ));
+    assert!(!school_open(
+      &sdds,
+      0,
+      MONDAY + 11 * HOUR,
+      MONDAY + 14 * HOUR
+    ));
+  }
+
+  #[test]
+  fn attendee_commitment() {
+    let sds = [session_data(1, 1, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR)];
+    let cs = [commitment(7, &sds[0])];
+    let ctx = context(&sds, &cs, &[], &[], &[]);
+
+    let mut props = session_new(2, MONDAY + 10 * HOUR + 30 * MINUTE, MONDAY + 12 * HOUR);
+    props.attendee_user_ids = vec![7, 8];
+    let conflicts = ctx.session_conflicts(&props);
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(
+      conflicts[0],
+      Conflict::AttendeeCommitment {
+        attendee_user_id: 7,
+        ..
+      }
+    ));
+    assert!(matches!(
+      conflicts[0].error(),
+      InnexgoHoursError::ConflictAttendeeCommitment
+    ));
+
+    props.start_time = MONDAY + 11 * HOUR;
+    assert!(ctx.session_conflicts(&props).is_empty());
+  }
+
+  #[test]
+  fn commitment_conflicts() {
+    let sds = [
+      session_data(1, 1, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR),
+      session_data(2, 2, MONDAY + 10 * HOUR, MONDAY + 12 * HOUR),
+    ];
+    let cs = [commitment(7, &sds[0])];
+    let ctx = context(&sds, &cs, &[], &[], &[]);
+
+    let mut props = CommitmentNewProps {
+      attendee_user_ids: vec![7],
+      session_id: 2,
+      idempotency_key: None,
+      api_key: Secret::default(),
+      active: true,
+    };
+    let conflicts = ctx.commitment_conflicts(&props);
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(
+      conflicts[0],
+      Conflict::AttendeeCommitment {
+        attendee_user_id: 7,
+        ref session_data,
+      } if session_data.session.session_id == 1
+    ));
+
+    // recommitting to the same session doesn't conflict with itself
+    props.session_id = 1;
+    assert!(ctx.commitment_conflicts(&props).is_empty());
+    // leaving a session never conflicts
+    props.session_id = 2;
+    props.active = false;
+    assert!(ctx.commitment_conflicts(&props).is_empty());
+  }
+
+  #[test]
+  fn instructor_session() {
+    // user 10 instructs course 1 and assists course 2, user 11 instructs course 3
+    let cms = [
+      course_membership(10, 1, CourseMembershipKind::Instructor),
+      course_membership(10, 2, CourseMembershipKind::TeachingAssistant),
+      course_membership(11, 3, CourseMembershipKind::Instructor),
+      course_membership(12, 3, CourseMembershipKind::Student),
+      course_membership(12, 1, CourseMembershipKind::Student),
+    ];
+    let sds = [
+      session_data(1, 2, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR),
+      session_data(2, 3, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR),
+    ];
+    let cds = [
+      course_data(1, 100),
+      course_data(2, 200),
+      course_data(3, 300),
+    ];
+    let ctx = context(&sds, &[], &cds, &cms, &[]);
+
+    let conflicts = ctx.session_conflicts(&session_new(1, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR));
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(
+      conflicts[0],
+      Conflict::InstructorSession {
+        instructor_user_id: 10,
+        ref session_data,
+      } if session_data.session.session_id == 1
+    ));
+    assert!(matches!(
+      conflicts[0].error(),
+      InnexgoHoursError::ConflictInstructorSession
+    ));
+  }
+
+  #[test]
+  fn location_session() {
+    let sds = [
+      session_data(1, 2, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR),
+      session_data(2, 3, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR),
+    ];
+    let cds = [
+      course_data(1, 100),
+      course_data(2, 100),
+      course_data(3, 300),
+    ];
+    let ctx = context(&sds, &[], &cds, &[], &[]);
+
+    let conflicts = ctx.session_conflicts(&session_new(1, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR));
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(
+      conflicts[0],
+      Conflict::LocationSession {
+        location_id: 100,
+        ref session_data,
+      } if session_data.session.session_id == 1
+    ));
+    assert!(matches!(
+      conflicts[0].error(),
+      InnexgoHoursError::ConflictLocationSession
+    ));
+  }
+
+  #[test]
+  fn school_closed() {
+    let sdds = [school_duration_data(1, 9 * 60, 17 * 60)];
+    let ctx = context(&[], &[], &[], &[], &sdds);
+
+    assert!(ctx
+      .session_conflicts(&session_new(1, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR))
+      .is_empty());
+    let conflicts = ctx.session_conflicts(&session_new(1, MONDAY + 16 * HOUR, MONDAY + 18 * HOUR));
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(conflicts[0], Conflict::SchoolClosed));
+    assert!(matches!(
+      conflicts[0].error(),
+      InnexgoHoursError::ConflictSchoolClosed
+    ));
+  }
+
+  #[test]
+  fn session_request_conflicts() {
+    let sds = [session_data(1, 1, MONDAY + 10 * HOUR, MONDAY + 11 * HOUR)];
+    let cs = [commitment(7, &sds[0])];
+    let sdds = [school_duration_data(1, 9 * 60, 17 * 60)];
+    let ctx = context(&sds, &cs, &[], &[], &sdds);
+
+    let mut props = SessionRequestNewProps {
+      course_id: 2,
+      message: String::new(),
+      start_time: MONDAY + 10 * HOUR,
+      end_time: MONDAY + 11 * HOUR,
+      idempotency_key: None,
+      api_key: Secret::default(),
+    };
+    let conflicts = ctx.session_request_conflicts(7, &props);
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(
+      conflicts[0],
+      Conflict::AttendeeCommitment {
+        attendee_user_id: 7,
+        ..
+      }
+    ));
+    assert!(ctx.session_request_conflicts(8, &props).is_empty());
+
+    props.start_time = MONDAY + 16 * HOUR;
+    props.end_time = MONDAY + 18 * HOUR;
+    let conflicts = ctx.session_request_conflicts(7, &props);
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(conflicts[0], Conflict::SchoolClosed));
+  }
+}
@@ -0,0 +1,123 @@
+// Decoding of server-sent event streams into typed events
+use super::response::InnexgoHoursError;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+
+// the data field of an event, if it has one
+// comments and keepalives have none
+fn event_data(event: &str) -> Option<String> {
+  let data: Vec<&str> = event
+    .lines()
+    .filter_map(|line| line.strip_prefix("data:"))
+    .map(|data| data.strip_prefix(' ').unwrap_or(data))
+    .collect();
+
+  if data.is_empty() {
+    None
+  } else {
+    Some(data.join("\n"))
+  }
+}
+
+// each event's data must be a json encoded T
+// the stream ends when the connection closes, or after yielding a network error
+pub fn decode<T: DeserializeOwned>(
+  resp: reqwest::Response,
+) -> impl Stream<Item = Result<T, InnexgoHoursError>> {
+  decode_chunks(resp.bytes_stream())
+}
+
+fn decode_chunks<T, S, B, E>(chunks: S) -> impl Stream<Item = Result<T, InnexgoHoursError>>
+where
+  T: DeserializeOwned,
+  S: Stream<Item = Result<B, E>>,
+  B: AsRef<[u8]>,
+{
+  let chunks = Box::pin(chunks);
+  stream::unfold(Some((chunks, Vec::new())), |state| async move {
+    let (mut chunks, mut buf) = state?;
+    loop {
+      // chunks may split events (or characters), so only decode complete events
+      if let Some(end) = buf.windows(2).position(|w| w == b"\n\n") {
+        let event: Vec<u8> = buf.drain(..end + 2).collect();
+        if let Some(data) = event_data(&String::from_utf8_lossy(&event)) {
+          let event = serde_json::from_str(&data).map_err(|_| InnexgoHoursError::DecodeError);
+          return Some((event, Some((chunks, buf))));
+        }
+        continue;
+      }
+
+      match chunks.next().await {
+        Some(Ok(chunk)) => buf.extend(chunk.as_ref().iter().filter(|&&b| b != b'\r')),
+        Some(Err(_)) => return Some((Err(InnexgoHoursError::Network), None)),
+        None => return None,
+      }
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::Value;
+
+  async fn decode_all(chunks: &[&str]) -> Vec<Result<Value, InnexgoHoursError>> {
+    let chunks = stream::iter(chunks.iter().map(|c| Ok::<_, ()>(c.as_bytes())));
+    decode_chunks(chunks).collect().await
+  }
+
+  fn values(events: Vec<Result<Value, InnexgoHoursError>>) -> Vec<Value> {
+    events.into_iter().map(Result::unwrap).collect()
+  }
+
+  #[tokio::test]
+  async fn event_split_across_chunks() {
+    let events = decode_all(&["da", "ta: {\"a\":", " 1}\n", "\ndata: 2\n\n"]).await;
+    assert_eq!(values(events), vec![serde_json::json!({"a": 1}), 2.into()]);
+  }
+
+  #[tokio::test]
+  async fn character_split_across_chunks() {
+    let json = "data: \"\u{e9}\"\n\n".as_bytes();
+    let chunks = stream::iter(vec![Ok::<_, ()>(&json[..8]), Ok(&json[8..])]);
+    let events: Vec<Result<Value, _>> = decode_chunks(chunks).collect().await;
+    assert_eq!(values(events), vec![Value::from("\u{e9}")]);
+  }
+
+  #[tokio::test]
+  async fn multi_line_data() {
+    let events = decode_all(&["data: [1,\ndata: 2]\n\n"]).await;
+    assert_eq!(values(events), vec![serde_json::json!([1, 2])]);
+  }
+
+  #[tokio::test]
+  async fn crlf_line_endings() {
+    let events = decode_all(&["event: x\r\ndata: 1\r\n\r", "\ndata: 2\r\n\r\n"]).await;
+    assert_eq!(values(events), vec![Value::from(1), Value::from(2)]);
+  }
+
+  #[tokio::test]
+  async fn keepalive_comments_are_skipped() {
+    let events = decode_all(&[": keepalive\n\n", ":\n\ndata: 1\n\n", ": keepalive\n\n"]).await;
+    assert_eq!(values(events), vec![Value::from(1)]);
+  }
+
+  #[tokio::test]
+  async fn invalid_json_is_a_decode_error() {
+    let events = decode_all(&["data: {\n\ndata: 1\n\n"]).await;
+    assert!(matches!(events[0], Err(InnexgoHoursError::DecodeError)));
+    assert_eq!(events[1].as_ref().unwrap(), &Value::from(1));
+  }
+
+  #[tokio::test]
+  async fn network_error_ends_the_stream() {
+    let chunks = stream::iter(vec![
+      Ok("data: 1\n\n".as_bytes()),
+      Err(()),
+      Ok("data: 2\n\n".as_bytes()),
+    ]);
+    let events: Vec<Result<Value, _>> = decode_chunks(chunks).collect().await;
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[1], Err(InnexgoHoursError::Network)));
+  }
+}
@@ -0,0 +1,126 @@
+// Records shared by the tests of several modules
+//
+// every record belongs to school 1 and was created by user 1 at time 0
+use super::request::CourseMembershipKind;
+use super::response::{
+  Commitment, Course, CourseData, CourseMembership, Location, School, SchoolDuration,
+  SchoolDurationData, Session, SessionData,
+};
+
+pub fn school() -> School {
+  School {
+    school_id: 1,
+    creator_user_id: 1,
+    creation_time: 0,
+    whole: true,
+  }
+}
+
+pub fn location(location_id: i64) -> Location {
+  Location {
+    location_id,
+    creation_time: 0,
+    creator_user_id: 1,
+    school: school(),
+  }
+}
+
+pub fn course(course_id: i64) -> Course {
+  Course {
+    course_id,
+    creator_user_id: 1,
+    creation_time: 0,
+    school: school(),
+  }
+}
+
+pub fn course_data(course_id: i64, location_id: i64) -> CourseData {
+  CourseData {
+    course_data_id: course_id,
+    creation_time: 0,
+    creator_user_id: 1,
+    course: course(course_id),
+    location: location(location_id),
+    name: String::from("Course"),
+    description: String::new(),
+    homeroom: false,
+    active: true,
+  }
+}
+
+pub fn course_membership(
+  user_id: i64,
+  course_id: i64,
+  course_membership_kind: CourseMembershipKind,
+) -> CourseMembership {
+  CourseMembership {
+    course_membership_id: user_id * 100 + course_id,
+    creation_time: 0,
+    creator_user_id: 1,
+    user_id,
+    course: course(course_id),
+    course_membership_kind,
+    course_key: None,
+  }
+}
+
+pub fn session(session_id: i64, course_id: i64) -> Session {
+  Session {
+    session_id,
+    creation_time: 0,
+    // deliberately not an instructor, only memberships make one
+    creator_user_id: 1,
+    course: course(course_id),
+  }
+}
+
+pub fn session_data(
+  session_id: i64,
+  course_id: i64,
+  start_time: i64,
+  end_time: i64,
+) -> SessionData {
+  SessionData {
+    session_data_id: session_id,
+    creation_time: 0,
+    creator_user_id: 1,
+    session: session(session_id, course_id),
+    name: String::from("Session"),
+    start_time,
+    end_time,
+    max_attendees: None,
+    commitment_count: 0,
+    waitlist_count: 0,
+    active: true,
+  }
+}
+
+// the commitment_id is attendee_user_id * 100 + session_id
+pub fn commitment(attendee_user_id: i64, session_data: &SessionData) -> Commitment {
+  Commitment {
+    commitment_id: attendee_user_id * 100 + session_data.session.session_id,
+    creation_time: 0,
+    creator_user_id: 1,
+    attendee_user_id,
+    session: session_data.session.clone(),
+    active: true,
+  }
+}
+
+pub fn school_duration_data(day: i64, minute_start: i64, minute_end: i64) -> SchoolDurationData {
+  SchoolDurationData {
+    school_duration_data_id: day,
+    creation_time: 0,
+    creator_user_id: 1,
+    school_duration: SchoolDuration {
+      school_duration_id: day,
+      creation_time: 0,
+      creator_user_id: 1,
+      school: school(),
+    },
+    day,
+    minute_start,
+    minute_end,
+    active: true,
+  }
+}
@@ -0,0 +1,186 @@
+// Finding time windows in which a group of users are all free
+use super::conflict::{instructor_user_ids, overlaps, school_open};
+use super::request::{FREE_WINDOW_MAX_RANGE, FREE_WINDOW_MIN_STEP};
+use super::response::{
+  Commitment, CourseMembership, FreeWindow, InnexgoHoursError, SchoolDurationData, SessionData,
+  SessionRequest,
+};
+
+#[derive(Clone, Debug)]
+pub struct FreeWindowQuery {
+  // the range searched
+  pub start_time: i64,
+  pub end_time: i64,
+  // the length of each window
+  pub duration: i64,
+  // windows start at multiples of step after start_time
+  pub step: i64,
+  // windows closest to this are ranked first, earliest first if None
+  pub preferred_start_time: Option<i64>,
+  pub count: usize,
+}
+
+impl FreeWindowQuery {
+  // windows as long as the request, ranked by closeness to the requested time
+  // for suggesting alternatives when answering a session request
+  pub fn for_session_request(
+    session_request: &SessionRequest,
+    start_time: i64,
+    end_time: i64,
+    step: i64,
+    count: usize,
+  ) -> Self {
+    FreeWindowQuery {
+      start_time,
+      end_time,
+      duration: session_request.end_time - session_request.start_time,
+      step,
+      preferred_start_time: Some(session_request.start_time),
+      count,
+    }
+  }
+}
+
+// the intervals in which any of the users are busy
+// users are busy during sessions they are committed to, and sessions of courses they instruct
+// instructors are defined as in ConflictContext, see conflict::instructor_user_ids
+// session_datas, commitments and course_memberships should be the most recent record of each (only_recent)
+pub fn busy_intervals(
+  user_ids: &[i64],
+  session_datas: &[SessionData],
+  commitments: &[Commitment],
+  course_memberships: &[CourseMembership],
+) -> Vec<(i64, i64)> {
+  session_datas
+    .iter()
+    .filter(|sd| sd.active)
+    .filter(|sd| {
+      instructor_user_ids(course_memberships, sd.session.course.course_id)
+        .iter()
+        .any(|id| user_ids.contains(id))
+        || commitments.iter().any(|c| {
+          c.active
+            && c.session.session_id == sd.session.session_id
+            && user_ids.contains(&c.attendee_user_id)
+        })
+    })
+    .map(|sd| (sd.start_time, sd.end_time))
+    .collect()
+}
+
+// candidate windows that overlap no busy interval and lie within the school's open hours, ranked
+// fails with FreeWindowRangeInvalid if the query is outside the limits of FreeWindowViewProps
+pub fn free_windows(
+  busy: &[(i64, i64)],
+  school_duration_datas: &[SchoolDurationData],
+  utc_offset: i64,
+  query: &FreeWindowQuery,
+) -> Result<Vec<FreeWindow>, InnexgoHoursError> {
+  if query.duration <= 0
+    || query.step < FREE_WINDOW_MIN_STEP
+    || query.end_time < query.start_time
+    || query.end_time - query.start_time > FREE_WINDOW_MAX_RANGE
+  {
+    return Err(InnexgoHoursError::FreeWindowRangeInvalid);
+  }
+
+  let mut windows = vec![];
+  let mut start_time = query.start_time;
+  while start_time + query.duration <= query.end_time {
+    let end_time = start_time + query.duration;
+    if busy
+      .iter()
+      .all(|&(s, e)| !overlaps(s, e, start_time, end_time))
+      && school_open(school_duration_datas, utc_offset, start_time, end_time)
+    {
+      windows.push(FreeWindow {
+        start_time,
+        end_time,
+      });
+    }
+    start_time += query.step;
+  }
+
+  if let Some(preferred_start_time) = query.preferred_start_time {
+    // stable, so ties stay earliest first
+    windows.sort_by_key(|w| (w.start_time - preferred_start_time).abs());
+  }
+  windows.truncate(query.count);
+  Ok(windows)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fixture;
+  use crate::request::CourseMembershipKind;
+
+  const MINUTE: i64 = 60 * 1000;
+
+  #[test]
+  fn busy_instructors_are_those_of_the_course() {
+    // created by admin 1, run by teaching assistant 2
+    let sds = [fixture::session_data(1, 1, 100, 200)];
+    let cms = [fixture::course_membership(
+      2,
+      1,
+      CourseMembershipKind::TeachingAssistant,
+    )];
+
+    assert!(busy_intervals(&[1], &sds, &[], &cms).is_empty());
+    assert_eq!(busy_intervals(&[2], &sds, &[], &cms), vec![(100, 200)]);
+  }
+
+  #[test]
+  fn free_windows_step_and_rank() {
+    let query = FreeWindowQuery {
+      start_time: 0,
+      end_time: 1000 * MINUTE,
+      duration: 100 * MINUTE,
+      step: 50 * MINUTE,
+      preferred_start_time: Some(500 * MINUTE),
+      count: 3,
+    };
+    let busy = [(450 * MINUTE, 600 * MINUTE)];
+    let windows = free_windows(&busy, &[], 0, &query).unwrap();
+    let starts: Vec<i64> = windows.iter().map(|w| w.start_time / MINUTE).collect();
+    assert_eq!(starts, vec![600, 350, 650]);
+  }
+
+  #[test]
+  fn free_windows_rejects_expensive_queries() {
+    let query = FreeWindowQuery {
+      start_time: 0,
+      end_time: FREE_WINDOW_MAX_RANGE,
+      duration: 60 * MINUTE,
+      step: FREE_WINDOW_MIN_STEP,
+      preferred_start_time: None,
+      count: 1,
+    };
+    assert!(free_windows(&[], &[], 0, &query).is_ok());
+
+    for query in [
+      FreeWindowQuery {
+        step: FREE_WINDOW_MIN_STEP - 1,
+        ..query.clone()
+      },
+      FreeWindowQuery {
+        end_time: FREE_WINDOW_MAX_RANGE + 1,
+        ..query.clone()
+      },
+      FreeWindowQuery {
+        end_time: -1,
+        ..query.clone()
+      },
+      FreeWindowQuery {
+        duration: 0,
+        ..query.clone()
+      },
+    ] {
+      assert!(matches!(
+        free_windows(&[], &[], 0, &query),
+        Err(InnexgoHoursError::FreeWindowRangeInvalid)
+      ));
+    }
+  }
+}
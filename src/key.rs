@@ -0,0 +1,277 @@
+// Format of school and course keys
+//
+// keys are 16 characters from an alphabet without ambiguous characters (no I, L, O or U),
+// written in groups of 4 (eg: 7KQ2-M9XD-4TNB-R3WF), and the last character is a checksum
+//
+// keys created before this format are opaque strings, which are still accepted by the server
+// see prepare for how the client tells them apart
+use super::response::InnexgoHoursError;
+use super::secret::Secret;
+use strum::AsRefStr;
+
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const KEY_LEN: usize = 16;
+const GROUP_LEN: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AsRefStr)]
+#[strum(serialize_all = "camelCase")]
+pub enum KeyKind {
+  SchoolKey,
+  CourseKey,
+}
+
+// uppercases and maps look-alikes (O to 0, I and L to 1)
+fn code_point(c: char) -> Option<usize> {
+  let c = match c.to_ascii_uppercase() {
+    'O' => '0',
+    'I' | 'L' => '1',
+    c => c,
+  };
+  ALPHABET.iter().position(|&a| a as char == c)
+}
+
+// luhn mod N over the alphabet
+fn luhn_sum(code_points: impl DoubleEndedIterator<Item = usize>, double_first: bool) -> usize {
+  let n = ALPHABET.len();
+  let mut double = double_first;
+  let mut sum = 0;
+  for code_point in code_points.rev() {
+    let addend = if double { code_point * 2 } else { code_point };
+    sum += addend / n + addend % n;
+    double = !double;
+  }
+  sum % n
+}
+
+#[cfg(any(feature = "keygen", test))]
+fn check_char(payload: &[usize]) -> char {
+  let n = ALPHABET.len();
+  ALPHABET[(n - luhn_sum(payload.iter().copied(), true)) % n] as char
+}
+
+fn group(key: &str) -> String {
+  key
+    .as_bytes()
+    .chunks(GROUP_LEN)
+    .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+// uppercases, drops separators and whitespace, and maps look-alikes (O to 0, I and L to 1)
+// returns the grouped key if it is well formed and its checksum matches
+pub fn normalize(key: &str) -> Option<Secret> {
+  let mut code_points = vec![];
+  for c in key.chars() {
+    match c {
+      '-' | ' ' | '\t' => continue,
+      c => code_points.push(code_point(c)?),
+    }
+  }
+
+  if code_points.len() != KEY_LEN || luhn_sum(code_points.iter().copied(), false) != 0 {
+    return None;
+  }
+
+  let key: String = code_points.iter().map(|&i| ALPHABET[i] as char).collect();
+  Some(Secret::from(group(&key)))
+}
+
+pub fn valid(key: &str) -> bool {
+  normalize(key).is_some()
+}
+
+// written like a key of this format (4 groups of 4 characters of the alphabet), valid or not
+fn grouped(key: &str) -> bool {
+  let groups: Vec<&str> = key.trim().split('-').collect();
+  groups.len() == KEY_LEN / GROUP_LEN
+    && groups
+      .iter()
+      .all(|g| g.chars().count() == GROUP_LEN && g.chars().all(|c| code_point(c).is_some()))
+}
+
+// the key to send to the server, as entered by a user
+// only grouped keys are normalized, and those with a bad checksum are typos (KeyMalformed)
+// anything else may be a key created before this format, and is sent unchanged
+// (about 1 in 32 ungrouped legacy keys would pass the checksum, so they must not be rewritten)
+pub fn prepare(key: &Secret) -> Result<Secret, InnexgoHoursError> {
+  if grouped(key.expose()) {
+    normalize(key.expose()).ok_or(InnexgoHoursError::KeyMalformed)
+  } else {
+    Ok(key.clone())
+  }
+}
+
+// a new random key, for use by the server
+#[cfg(feature = "keygen")]
+pub fn generate() -> Secret {
+  use rand::rngs::OsRng;
+  use rand::Rng;
+
+  let payload: Vec<usize> = (0..KEY_LEN - 1)
+    .map(|_| OsRng.gen_range(0..ALPHABET.len()))
+    .collect();
+
+  let mut key: String = payload.iter().map(|&i| ALPHABET[i] as char).collect();
+  key.push(check_char(&payload));
+  Secret::from(group(&key))
+}
+
+// the contents of a qr code to project, which opens the join page at base_url
+// eg: https://hours.example.com/join?courseKey=7KQ2-M9XD-4TNB-R3WF
+pub fn qr_payload(base_url: &str, key_kind: KeyKind, key: &Secret) -> String {
+  format!(
+    "{}/join?{}={}",
+    base_url.trim_end_matches('/'),
+    key_kind.as_ref(),
+    key.expose()
+  )
+}
+
+// the inverse of qr_payload, validating the key
+pub fn parse_qr_payload(payload: &str) -> Option<(KeyKind, Secret)> {
+  let (_, query) = payload.split_once("/join?")?;
+  query.split('&').find_map(|param| {
+    let (name, value) = param.split_once('=')?;
+    let key_kind = match name {
+      x if x == KeyKind::SchoolKey.as_ref() => KeyKind::SchoolKey,
+      x if x == KeyKind::CourseKey.as_ref() => KeyKind::CourseKey,
+      _ => return None,
+    };
+    Some((key_kind, normalize(value)?))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // appends the check character to a 15 character payload
+  fn with_check(payload: &str) -> String {
+    let code_points: Vec<usize> = payload.chars().map(|c| code_point(c).unwrap()).collect();
+    let mut key = String::from(payload);
+    key.push(check_char(&code_points));
+    group(&key)
+  }
+
+  #[cfg(feature = "keygen")]
+  #[test]
+  fn generated_keys_are_valid() {
+    for _ in 0..1000 {
+      let key = generate();
+      assert!(valid(key.expose()), "{}", key.expose());
+      assert_eq!(normalize(key.expose()), Some(key.clone()));
+      assert_eq!(key.expose().len(), KEY_LEN + KEY_LEN / GROUP_LEN - 1);
+    }
+  }
+
+  #[test]
+  fn check_char_makes_a_valid_key() {
+    let key = with_check("7KQ2M9XD4TNBR3W");
+    assert!(valid(&key));
+  }
+
+  #[test]
+  fn single_substitutions_are_detected() {
+    let key: Vec<char> = with_check("7KQ2M9XD4TNBR3W")
+      .chars()
+      .filter(|&c| c != '-')
+      .collect();
+    for i in 0..KEY_LEN {
+      for &a in ALPHABET {
+        let a = a as char;
+        if a == key[i] {
+          continue;
+        }
+        let mut substituted = key.clone();
+        substituted[i] = a;
+        let substituted: String = substituted.into_iter().collect();
+        assert!(!valid(&substituted), "{}", substituted);
+      }
+    }
+  }
+
+  #[test]
+  fn look_alikes_are_mapped() {
+    let key = with_check("0011ABCDEFGHJKM");
+    assert!(key.starts_with("0011-ABCD-EFGH-JKM"));
+    let typed = key
+      .replacen('0', "O", 1)
+      .replacen('0', "o", 1)
+      .replacen('1', "I", 1)
+      .replacen('1', "l", 1)
+      .to_lowercase();
+    assert_eq!(normalize(&typed), Some(Secret::from(key.as_str())));
+    let spaced = key.replace('-', " ");
+    assert_eq!(normalize(&spaced), Some(Secret::from(key.as_str())));
+    assert_eq!(normalize(&key.replace('-', "")), Some(Secret::from(key)));
+  }
+
+  #[test]
+  fn malformed_keys() {
+    let key = with_check("7KQ2M9XD4TNBR3W");
+    assert!(!valid(&key[..key.len() - 1]));
+    assert!(!valid(&format!("{}0", key)));
+    assert!(!valid(&key.replacen('7', "U", 1)));
+    assert!(!valid(""));
+  }
+
+  #[test]
+  fn qr_payload_round_trip() {
+    let key = Secret::from(with_check("7KQ2M9XD4TNBR3W"));
+    for key_kind in [KeyKind::SchoolKey, KeyKind::CourseKey] {
+      let payload = qr_payload("https://hours.example.com/", key_kind, &key);
+      assert_eq!(
+        payload,
+        format!(
+          "https://hours.example.com/join?{}={}",
+          key_kind.as_ref(),
+          key.expose()
+        )
+      );
+      assert_eq!(parse_qr_payload(&payload), Some((key_kind, key.clone())));
+    }
+    assert_eq!(
+      parse_qr_payload("https://hours.example.com/join?other=1"),
+      None
+    );
+    let bad = qr_payload(
+      "https://hours.example.com",
+      KeyKind::CourseKey,
+      &Secret::from("0000-0000-0000-0001"),
+    );
+    assert_eq!(parse_qr_payload(&bad), None);
+  }
+
+  #[test]
+  fn prepare_keys() {
+    let key = with_check("7KQ2M9XD4TNBR3W");
+    assert_eq!(
+      prepare(&Secret::from(key.to_lowercase())).unwrap(),
+      Secret::from(key.as_str())
+    );
+    // a typo in a key of this format
+    let typo = key.replacen('7', "8", 1);
+    assert!(matches!(
+      prepare(&Secret::from(typo)),
+      Err(InnexgoHoursError::KeyMalformed)
+    ));
+    // keys created before this format are sent unchanged
+    // including ungrouped ones which happen to pass the checksum
+    let hex = "873a0000c6d07ef7";
+    assert!(valid(hex));
+    let ungrouped = key.replace('-', "");
+    for legacy in [
+      "a1b2c3d4e5f6a7b8c9d0",
+      "Xy_9-legacy",
+      "4f3c2a1b",
+      hex,
+      &ungrouped,
+    ] {
+      assert_eq!(
+        prepare(&Secret::from(legacy)).unwrap(),
+        Secret::from(legacy)
+      );
+    }
+  }
+}
@@ -1,6 +1,26 @@
 // Types of arguments for auth handlers
 pub mod response;
 pub mod request;
+pub mod secret;
+pub mod subscription;
+pub mod booking;
+pub mod capability;
+pub mod conflict;
+pub mod free_window;
+pub mod key;
+pub mod notification;
+pub mod reminder;
 // not all conumers need a client
 #[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "client")]
+pub mod cache;
+#[cfg(feature = "client")]
+pub mod change_feed;
+#[cfg(feature = "client")]
+pub mod event_stream;
+// not all consumers receive webhooks
+#[cfg(feature = "webhook")]
+pub mod webhook;
+#[cfg(test)]
+mod fixture;
@@ -0,0 +1,375 @@
+// Notifications sent to students and instructors about their sessions
+use super::response::{InnexgoHoursError, SessionData, SessionRequest, SessionRequestResponse};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use strum::AsRefStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum NotificationKind {
+  SessionRequestReceived,
+  SessionRequestAccepted,
+  SessionRequestDeclined,
+  SessionCancelled,
+  WaitlistPromoted,
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+  pub notification_kind: NotificationKind,
+  pub subject: String,
+  pub text: String,
+  pub html: String,
+}
+
+// delivers rendered notifications, eg: over email
+// implementations should return EmailRatelimit or EmailBlacklisted where appropriate
+pub trait NotificationSender {
+  fn send(&self, to: &str, notification: &Notification) -> Result<(), InnexgoHoursError>;
+}
+
+// writes each notification to a pair of files in dir instead of sending it
+// useful for tests and local development
+pub struct FileNotificationSender {
+  dir: PathBuf,
+  count: AtomicU64,
+}
+
+impl FileNotificationSender {
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    FileNotificationSender {
+      dir: dir.into(),
+      count: AtomicU64::new(0),
+    }
+  }
+}
+
+impl NotificationSender for FileNotificationSender {
+  fn send(&self, to: &str, notification: &Notification) -> Result<(), InnexgoHoursError> {
+    let n = self.count.fetch_add(1, Ordering::Relaxed);
+    let name = format!("{}-{}", n, notification.notification_kind.as_ref());
+    let text = format!(
+      "To: {}\nSubject: {}\n\n{}",
+      to, notification.subject, notification.text
+    );
+    fs::write(self.dir.join(format!("{}.txt", name)), text)
+      .and_then(|_| fs::write(self.dir.join(format!("{}.html", name)), &notification.html))
+      .map_err(|_| InnexgoHoursError::Unknown)
+  }
+}
+
+fn escape_html(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+// formats a millisecond timestamp as "YYYY-MM-DD HH:MM UTC"
+// callers that know the school's timezone should pass their own formatter instead
+pub fn format_time_utc(millis: i64) -> String {
+  let secs = millis.div_euclid(1000);
+  let days = secs.div_euclid(86400);
+  let secs_of_day = secs.rem_euclid(86400);
+
+  // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+  format!(
+    "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+    year,
+    month,
+    day,
+    secs_of_day / 3600,
+    secs_of_day % 3600 / 60
+  )
+}
+
+// fills in the shared layout, lines are plain text and escaped for the html version
+fn render(notification_kind: NotificationKind, subject: String, lines: &[String]) -> Notification {
+  let html_lines: Vec<String> = lines
+    .iter()
+    .map(|line| format!("<p>{}</p>", escape_html(line)))
+    .collect();
+
+  Notification {
+    notification_kind,
+    html: format!(
+      "<html><body><h2>{}</h2>{}</body></html>",
+      escape_html(&subject),
+      html_lines.join("")
+    ),
+    text: lines.join("\n\n"),
+    subject,
+  }
+}
+
+// sent to the course's instructors
+pub fn session_request_received(
+  session_request: &SessionRequest,
+  format_time: impl Fn(i64) -> String,
+) -> Notification {
+  render(
+    NotificationKind::SessionRequestReceived,
+    String::from("New session request"),
+    &[
+      format!(
+        "A student has requested a session from {} to {}.",
+        format_time(session_request.start_time),
+        format_time(session_request.end_time)
+      ),
+      format!("Message: {}", session_request.message),
+    ],
+  )
+}
+
+// sent to the student who made the request
+// session_data describes the session the commitment is for, if the request was accepted
+pub fn session_request_responded(
+  session_request_response: &SessionRequestResponse,
+  session_data: Option<&SessionData>,
+  format_time: impl Fn(i64) -> String,
+) -> Notification {
+  let session_request = &session_request_response.session_request;
+  let requested = format!(
+    "You requested a session from {} to {}.",
+    format_time(session_request.start_time),
+    format_time(session_request.end_time)
+  );
+  let message = format!("Message: {}", session_request_response.message);
+
+  match (&session_request_response.commitment, session_data) {
+    (Some(_), Some(session_data)) => render(
+      NotificationKind::SessionRequestAccepted,
+      format!("Session request accepted: {}", session_data.name),
+      &[
+        requested,
+        format!(
+          "You have been added to {}, from {} to {}.",
+          session_data.name,
+          format_time(session_data.start_time),
+          format_time(session_data.end_time)
+        ),
+        message,
+      ],
+    ),
+    (Some(_), None) => render(
+      NotificationKind::SessionRequestAccepted,
+      String::from("Session request accepted"),
+      &[requested, message],
+    ),
+    (None, _) => render(
+      NotificationKind::SessionRequestDeclined,
+      String::from("Session request declined"),
+      &[requested, message],
+    ),
+  }
+}
+
+// sent to each attendee with an active commitment once session_data is no longer active
+pub fn session_cancelled(
+  session_data: &SessionData,
+  format_time: impl Fn(i64) -> String,
+) -> Notification {
+  render(
+    NotificationKind::SessionCancelled,
+    format!("Session cancelled: {}", session_data.name),
+    &[format!(
+      "{}, scheduled from {} to {}, has been cancelled.",
+      session_data.name,
+      format_time(session_data.start_time),
+      format_time(session_data.end_time)
+    )],
+  )
+}
+
+// sent to the attendee once their waitlist entry is promoted into a commitment
+// session_data describes the session of the waitlist entry
+pub fn waitlist_promoted(
+  session_data: &SessionData,
+  format_time: impl Fn(i64) -> String,
+) -> Notification {
+  render(
+    NotificationKind::WaitlistPromoted,
+    format!("You're in: {}", session_data.name),
+    &[format!(
+      "A spot opened up in {}, from {} to {}, and you have been moved off the waitlist.",
+      session_data.name,
+      format_time(session_data.start_time),
+      format_time(session_data.end_time)
+    )],
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fixture;
+
+  // 2024-01-01T10:00:00Z
+  const START: i64 = 1_704_103_200_000;
+  const HOUR: i64 = 60 * 60 * 1000;
+
+  fn session_data() -> SessionData {
+    let mut session_data = fixture::session_data(1, 1, START, START + HOUR);
+    session_data.name = String::from("Calc <AP> & \"Review\"");
+    session_data
+  }
+
+  fn session_request(message: &str) -> SessionRequest {
+    SessionRequest {
+      session_request_id: 1,
+      creation_time: 0,
+      creator_user_id: 2,
+      course: fixture::course(1),
+      message: String::from(message),
+      start_time: START,
+      end_time: START + HOUR,
+    }
+  }
+
+  fn session_request_response(accepted: bool) -> SessionRequestResponse {
+    let session_data = session_data();
+    SessionRequestResponse {
+      session_request: session_request("please"),
+      creation_time: 0,
+      creator_user_id: 1,
+      message: String::from("see you there"),
+      commitment: accepted.then(|| fixture::commitment(2, &session_data)),
+    }
+  }
+
+  #[test]
+  fn format_time_utc_known_dates() {
+    assert_eq!(format_time_utc(0), "1970-01-01 00:00 UTC");
+    assert_eq!(format_time_utc(START), "2024-01-01 10:00 UTC");
+    // leap day, and seconds are truncated
+    assert_eq!(format_time_utc(951_782_459_000), "2000-02-29 00:00 UTC");
+    assert_eq!(format_time_utc(4_107_542_340_000), "2100-02-28 23:59 UTC");
+    assert_eq!(format_time_utc(-1), "1969-12-31 23:59 UTC");
+  }
+
+  #[test]
+  fn escape_html_escapes_markup() {
+    assert_eq!(
+      escape_html(r#"<a href="x">Tom & Jerry's</a>"#),
+      "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+    );
+  }
+
+  #[test]
+  fn session_request_received_renders() {
+    let n = session_request_received(&session_request("<b>help</b>"), format_time_utc);
+    assert_eq!(
+      n.notification_kind,
+      NotificationKind::SessionRequestReceived
+    );
+    assert_eq!(n.subject, "New session request");
+    assert!(n
+      .text
+      .contains("from 2024-01-01 10:00 UTC to 2024-01-01 11:00 UTC"));
+    assert!(n.text.contains("Message: <b>help</b>"));
+    assert!(n.html.contains("Message: &lt;b&gt;help&lt;/b&gt;"));
+    assert!(!n.html.contains("<b>"));
+  }
+
+  #[test]
+  fn session_request_responded_renders() {
+    let session_data = session_data();
+    let n = session_request_responded(
+      &session_request_response(true),
+      Some(&session_data),
+      format_time_utc,
+    );
+    assert_eq!(
+      n.notification_kind,
+      NotificationKind::SessionRequestAccepted
+    );
+    assert_eq!(
+      n.subject,
+      "Session request accepted: Calc <AP> & \"Review\""
+    );
+    assert!(n.text.contains("You have been added to Calc <AP>"));
+    assert!(n
+      .html
+      .contains("<h2>Session request accepted: Calc &lt;AP&gt; &amp; &quot;Review&quot;</h2>"));
+
+    let n = session_request_responded(&session_request_response(true), None, format_time_utc);
+    assert_eq!(
+      n.notification_kind,
+      NotificationKind::SessionRequestAccepted
+    );
+    assert_eq!(n.subject, "Session request accepted");
+
+    let n = session_request_responded(
+      &session_request_response(false),
+      Some(&session_data),
+      format_time_utc,
+    );
+    assert_eq!(
+      n.notification_kind,
+      NotificationKind::SessionRequestDeclined
+    );
+    assert!(n.text.contains("Message: see you there"));
+  }
+
+  #[test]
+  fn session_cancelled_renders() {
+    let n = session_cancelled(&session_data(), format_time_utc);
+    assert_eq!(n.notification_kind, NotificationKind::SessionCancelled);
+    assert!(n
+      .text
+      .contains("scheduled from 2024-01-01 10:00 UTC to 2024-01-01 11:00 UTC"));
+    assert!(n.html.contains("Calc &lt;AP&gt;"));
+  }
+
+  #[test]
+  fn waitlist_promoted_renders() {
+    let n = waitlist_promoted(&session_data(), format_time_utc);
+    assert_eq!(n.notification_kind, NotificationKind::WaitlistPromoted);
+    assert_eq!(n.subject, "You're in: Calc <AP> & \"Review\"");
+    assert!(n.html.contains("You&#39;re in"));
+  }
+
+  #[test]
+  fn file_sender_writes_text_and_html() {
+    let dir = std::env::temp_dir().join(format!("notification-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir(&dir).unwrap();
+
+    let sender = FileNotificationSender::new(&dir);
+    let n = session_cancelled(&session_data(), format_time_utc);
+    sender.send("student@example.com", &n).unwrap();
+    sender.send("other@example.com", &n).unwrap();
+
+    let text = fs::read_to_string(dir.join("0-session_cancelled.txt")).unwrap();
+    assert!(text.starts_with("To: student@example.com\nSubject: Session cancelled: "));
+    assert!(text.ends_with(&n.text));
+    let html = fs::read_to_string(dir.join("0-session_cancelled.html")).unwrap();
+    assert_eq!(html, n.html);
+    assert!(dir.join("1-session_cancelled.txt").exists());
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(matches!(
+      sender.send("student@example.com", &n),
+      Err(InnexgoHoursError::Unknown)
+    ));
+  }
+}
@@ -0,0 +1,400 @@
+// Reminders sent to attendees ahead of their sessions
+use super::response::{Commitment, InnexgoHoursError, SessionData};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+const MINUTE: i64 = 60 * 1000;
+const HOUR: i64 = 60 * MINUTE;
+
+#[derive(Clone, Debug)]
+pub struct ReminderConfig {
+  // milliseconds before a session's start_time at which to remind
+  pub offsets: Vec<i64>,
+}
+
+impl Default for ReminderConfig {
+  fn default() -> Self {
+    ReminderConfig {
+      offsets: vec![24 * HOUR, 15 * MINUTE],
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReminderJob {
+  pub commitment: Commitment,
+  pub session_data: SessionData,
+  pub offset: i64,
+  pub send_time: i64,
+}
+
+// stable across restarts, used to avoid sending the same reminder twice
+// includes the start_time, so rescheduling a session makes its reminders due again
+fn reminder_key(commitment_id: i64, start_time: i64, offset: i64) -> String {
+  format!("{}:{}:{}", commitment_id, start_time, offset)
+}
+
+impl ReminderJob {
+  pub fn key(&self) -> String {
+    reminder_key(
+      self.commitment.commitment_id,
+      self.session_data.start_time,
+      self.offset,
+    )
+  }
+}
+
+// remembers which reminders have already been handled
+pub trait ReminderStore {
+  fn contains(&self, key: &str) -> bool;
+  fn insert(&mut self, key: &str) -> Result<(), InnexgoHoursError>;
+}
+
+pub trait ReminderDelivery {
+  fn deliver(&self, job: &ReminderJob) -> Result<(), InnexgoHoursError>;
+}
+
+// keeps handled keys in memory, forgetting them on restart
+#[derive(Clone, Debug, Default)]
+pub struct MemoryReminderStore {
+  keys: HashSet<String>,
+}
+
+impl ReminderStore for MemoryReminderStore {
+  fn contains(&self, key: &str) -> bool {
+    self.keys.contains(key)
+  }
+
+  fn insert(&mut self, key: &str) -> Result<(), InnexgoHoursError> {
+    self.keys.insert(key.to_owned());
+    Ok(())
+  }
+}
+
+// appends handled keys to a file, one per line
+pub struct FileReminderStore {
+  path: PathBuf,
+  keys: HashSet<String>,
+}
+
+impl FileReminderStore {
+  pub fn open(path: impl Into<PathBuf>) -> Result<Self, InnexgoHoursError> {
+    let path = path.into();
+    let keys = match fs::read_to_string(&path) {
+      Ok(contents) => contents.lines().map(String::from).collect(),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+      Err(_) => return Err(InnexgoHoursError::Unknown),
+    };
+    Ok(FileReminderStore { path, keys })
+  }
+}
+
+impl ReminderStore for FileReminderStore {
+  fn contains(&self, key: &str) -> bool {
+    self.keys.contains(key)
+  }
+
+  fn insert(&mut self, key: &str) -> Result<(), InnexgoHoursError> {
+    OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.path)
+      .and_then(|mut file| writeln!(file, "{}", key))
+      .map_err(|_| InnexgoHoursError::Unknown)?;
+    self.keys.insert(key.to_owned());
+    Ok(())
+  }
+}
+
+// every reminder that is due at now, for active commitments to active sessions that haven't started
+// session_datas and commitments should be the most recent record of each (only_recent)
+// if several offsets are due for one commitment, only the closest to the start is returned
+pub fn due_reminders(
+  config: &ReminderConfig,
+  session_datas: &[SessionData],
+  commitments: &[Commitment],
+  now: i64,
+) -> Vec<ReminderJob> {
+  let mut jobs = vec![];
+  for commitment in commitments.iter().filter(|c| c.active) {
+    let session_data = session_datas
+      .iter()
+      .find(|sd| sd.session.session_id == commitment.session.session_id);
+
+    let session_data = match session_data {
+      Some(session_data) if session_data.active && session_data.start_time > now => session_data,
+      _ => continue,
+    };
+
+    let offset = config
+      .offsets
+      .iter()
+      .copied()
+      .filter(|offset| session_data.start_time - offset <= now)
+      .min();
+
+    if let Some(offset) = offset {
+      jobs.push(ReminderJob {
+        commitment: commitment.clone(),
+        session_data: session_data.clone(),
+        offset,
+        send_time: session_data.start_time - offset,
+      });
+    }
+  }
+  jobs
+}
+
+pub struct ReminderScheduler<S: ReminderStore, D: ReminderDelivery> {
+  config: ReminderConfig,
+  store: S,
+  delivery: D,
+}
+
+impl<S: ReminderStore, D: ReminderDelivery> ReminderScheduler<S, D> {
+  pub fn new(config: ReminderConfig, store: S, delivery: D) -> Self {
+    ReminderScheduler {
+      config,
+      store,
+      delivery,
+    }
+  }
+
+  // delivers each due reminder that has not already been handled
+  // also marks the larger offsets that were skipped, so they are not sent later
+  // returns the jobs that failed to deliver, they will be retried on the next scan
+  pub fn scan(
+    &mut self,
+    session_datas: &[SessionData],
+    commitments: &[Commitment],
+    now: i64,
+  ) -> Vec<(ReminderJob, InnexgoHoursError)> {
+    let mut failures = vec![];
+    for job in due_reminders(&self.config, session_datas, commitments, now) {
+      if self.store.contains(&job.key()) {
+        continue;
+      }
+
+      if let Err(e) = self
+        .delivery
+        .deliver(&job)
+        .and_then(|_| self.mark_handled(&job))
+      {
+        failures.push((job, e));
+      }
+    }
+    failures
+  }
+
+  fn mark_handled(&mut self, job: &ReminderJob) -> Result<(), InnexgoHoursError> {
+    for &offset in self.config.offsets.iter().filter(|&&o| o >= job.offset) {
+      self.store.insert(&reminder_key(
+        job.commitment.commitment_id,
+        job.session_data.start_time,
+        offset,
+      ))?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fixture;
+  use std::cell::{Cell, RefCell};
+
+  const START: i64 = 1_700_000_000_000;
+  // see fixture::commitment
+  const COMMITMENT_ID: i64 = 201;
+
+  fn session_data(start_time: i64, active: bool) -> SessionData {
+    let mut session_data = fixture::session_data(1, 1, start_time, start_time + HOUR);
+    session_data.active = active;
+    session_data
+  }
+
+  fn commitment(session_data: &SessionData, active: bool) -> Commitment {
+    let mut commitment = fixture::commitment(2, session_data);
+    commitment.active = active;
+    commitment
+  }
+
+  // one active commitment to a session starting at START
+  fn fixture() -> (Vec<SessionData>, Vec<Commitment>) {
+    let sd = session_data(START, true);
+    let c = commitment(&sd, true);
+    (vec![sd], vec![c])
+  }
+
+  // records the key of each delivered job, failing every delivery while fail is set
+  #[derive(Default)]
+  struct TestDelivery {
+    fail: Cell<bool>,
+    delivered: RefCell<Vec<String>>,
+  }
+
+  impl ReminderDelivery for &TestDelivery {
+    fn deliver(&self, job: &ReminderJob) -> Result<(), InnexgoHoursError> {
+      if self.fail.get() {
+        return Err(InnexgoHoursError::Network);
+      }
+      self.delivered.borrow_mut().push(job.key());
+      Ok(())
+    }
+  }
+
+  fn key(offset: i64) -> String {
+    reminder_key(COMMITMENT_ID, START, offset)
+  }
+
+  fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("reminder-{}-{}", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+  }
+
+  #[test]
+  fn nothing_due_before_the_largest_offset() {
+    let (sds, cs) = fixture();
+    let now = START - 24 * HOUR - 1;
+    assert!(due_reminders(&ReminderConfig::default(), &sds, &cs, now).is_empty());
+  }
+
+  #[test]
+  fn only_active_commitments_to_active_unstarted_sessions() {
+    let config = ReminderConfig::default();
+    let now = START - MINUTE;
+    let (sds, cs) = fixture();
+    assert_eq!(due_reminders(&config, &sds, &cs, now).len(), 1);
+    assert!(due_reminders(&config, &sds, &cs, START).is_empty());
+
+    let inactive = vec![commitment(&sds[0], false)];
+    assert!(due_reminders(&config, &sds, &inactive, now).is_empty());
+
+    let cancelled = vec![session_data(START, false)];
+    assert!(due_reminders(&config, &cancelled, &cs, now).is_empty());
+  }
+
+  #[test]
+  fn each_offset_is_sent_once() {
+    let (sds, cs) = fixture();
+    let delivery = TestDelivery::default();
+    let mut scheduler = ReminderScheduler::new(
+      ReminderConfig::default(),
+      MemoryReminderStore::default(),
+      &delivery,
+    );
+
+    for now in [
+      START - 23 * HOUR,
+      START - 22 * HOUR,
+      START - 10 * MINUTE,
+      START - MINUTE,
+    ] {
+      assert!(scheduler.scan(&sds, &cs, now).is_empty());
+    }
+    assert_eq!(
+      *delivery.delivered.borrow(),
+      vec![key(24 * HOUR), key(15 * MINUTE)]
+    );
+  }
+
+  #[test]
+  fn skipped_larger_offsets_are_marked() {
+    let path = temp_path("skipped");
+    let (sds, cs) = fixture();
+    let delivery = TestDelivery::default();
+
+    let store = FileReminderStore::open(&path).unwrap();
+    let mut scheduler = ReminderScheduler::new(ReminderConfig::default(), store, &delivery);
+    assert!(scheduler.scan(&sds, &cs, START - 10 * MINUTE).is_empty());
+    assert!(scheduler.scan(&sds, &cs, START - 5 * MINUTE).is_empty());
+    assert_eq!(*delivery.delivered.borrow(), vec![key(15 * MINUTE)]);
+
+    let store = FileReminderStore::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(store.contains(&key(24 * HOUR)));
+    assert!(store.contains(&key(15 * MINUTE)));
+  }
+
+  #[test]
+  fn failed_deliveries_are_retried() {
+    let (sds, cs) = fixture();
+    let delivery = TestDelivery::default();
+    let mut scheduler = ReminderScheduler::new(
+      ReminderConfig::default(),
+      MemoryReminderStore::default(),
+      &delivery,
+    );
+
+    delivery.fail.set(true);
+    let failures = scheduler.scan(&sds, &cs, START - 23 * HOUR);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0.key(), key(24 * HOUR));
+    assert!(matches!(failures[0].1, InnexgoHoursError::Network));
+
+    delivery.fail.set(false);
+    assert!(scheduler.scan(&sds, &cs, START - 22 * HOUR).is_empty());
+    assert!(scheduler.scan(&sds, &cs, START - 21 * HOUR).is_empty());
+    assert_eq!(*delivery.delivered.borrow(), vec![key(24 * HOUR)]);
+  }
+
+  #[test]
+  fn restart_with_file_store_does_not_resend() {
+    let path = temp_path("restart");
+    let (sds, cs) = fixture();
+    let delivery = TestDelivery::default();
+
+    let store = FileReminderStore::open(&path).unwrap();
+    let mut scheduler = ReminderScheduler::new(ReminderConfig::default(), store, &delivery);
+    assert!(scheduler.scan(&sds, &cs, START - 23 * HOUR).is_empty());
+    drop(scheduler);
+
+    let store = FileReminderStore::open(&path).unwrap();
+    let mut scheduler = ReminderScheduler::new(ReminderConfig::default(), store, &delivery);
+    assert!(scheduler.scan(&sds, &cs, START - 22 * HOUR).is_empty());
+    assert!(scheduler.scan(&sds, &cs, START - 10 * MINUTE).is_empty());
+    drop(scheduler);
+
+    let store = FileReminderStore::open(&path).unwrap();
+    let mut scheduler = ReminderScheduler::new(ReminderConfig::default(), store, &delivery);
+    assert!(scheduler.scan(&sds, &cs, START - 5 * MINUTE).is_empty());
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+      *delivery.delivered.borrow(),
+      vec![key(24 * HOUR), key(15 * MINUTE)]
+    );
+  }
+
+  #[test]
+  fn rescheduled_sessions_are_reminded_again() {
+    let (sds, cs) = fixture();
+    let delivery = TestDelivery::default();
+    let mut scheduler = ReminderScheduler::new(
+      ReminderConfig::default(),
+      MemoryReminderStore::default(),
+      &delivery,
+    );
+    assert!(scheduler.scan(&sds, &cs, START - 23 * HOUR).is_empty());
+
+    // moved two days later, after the 24h reminder went out
+    let moved = START + 48 * HOUR;
+    let sds = vec![session_data(moved, true)];
+    assert!(scheduler.scan(&sds, &cs, START - 22 * HOUR).is_empty());
+    assert!(scheduler.scan(&sds, &cs, moved - 23 * HOUR).is_empty());
+    assert!(scheduler.scan(&sds, &cs, moved - 10 * MINUTE).is_empty());
+
+    assert_eq!(
+      *delivery.delivered.borrow(),
+      vec![
+        key(24 * HOUR),
+        reminder_key(COMMITMENT_ID, moved, 24 * HOUR),
+        reminder_key(COMMITMENT_ID, moved, 15 * MINUTE),
+      ]
+    );
+  }
+}
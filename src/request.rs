@@ -1,4 +1,9 @@
 // Types of arguments for auth handlers
+//
+// api_key may be left empty (or omitted) when the key is sent as a bearer token
+// in the Authorization header instead, see InnexgoHoursService::with_api_key
+// an empty api_key is left out of the serialized body entirely
+use super::secret::Secret;
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use strum::AsRefStr;
@@ -21,11 +26,47 @@ impl TryFrom<u8> for SubscriptionKind {
   }
 }
 
+// limits of each plan are defined in the subscription module
+#[derive(Clone, Debug, Default, Serialize, Deserialize, AsRefStr)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SubscriptionPlan {
+  #[default]
+  Free,
+  Basic,
+  Standard,
+  Enterprise,
+  // subscriptions created before plans, whose limits are those they were created with
+  Legacy,
+}
+
+impl TryFrom<u8> for SubscriptionPlan {
+  type Error = u8;
+  fn try_from(val: u8) -> Result<SubscriptionPlan, u8> {
+    match val {
+      x if x == SubscriptionPlan::Free as u8 => Ok(SubscriptionPlan::Free),
+      x if x == SubscriptionPlan::Basic as u8 => Ok(SubscriptionPlan::Basic),
+      x if x == SubscriptionPlan::Standard as u8 => Ok(SubscriptionPlan::Standard),
+      x if x == SubscriptionPlan::Enterprise as u8 => Ok(SubscriptionPlan::Enterprise),
+      x if x == SubscriptionPlan::Legacy as u8 => Ok(SubscriptionPlan::Legacy),
+      x => Err(x),
+    }
+  }
+}
+
+// the plan of subscriptions stored before plans were added
+pub fn legacy_subscription_plan() -> SubscriptionPlan {
+  SubscriptionPlan::Legacy
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, AsRefStr)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum AdminshipKind {
   Admin,
   Cancel,
+  // manages locations and encounters, but cannot create keys or archive the school
+  FrontOffice,
+  // read only access to attendance in every course
+  Counselor,
 }
 
 impl TryFrom<u8> for AdminshipKind {
@@ -34,17 +75,28 @@ impl TryFrom<u8> for AdminshipKind {
     match val {
       x if x == AdminshipKind::Admin as u8 => Ok(AdminshipKind::Admin),
       x if x == AdminshipKind::Cancel as u8 => Ok(AdminshipKind::Cancel),
+      x if x == AdminshipKind::FrontOffice as u8 => Ok(AdminshipKind::FrontOffice),
+      x if x == AdminshipKind::Counselor as u8 => Ok(AdminshipKind::Counselor),
       x => Err(x),
     }
   }
 }
 
+// the kind of school keys made before kinds were added, which were all admin keys
+pub fn legacy_adminship_kind() -> AdminshipKind {
+  AdminshipKind::Admin
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, AsRefStr)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum CourseMembershipKind {
   Student,
   Instructor,
   Cancel,
+  // runs sessions and records encounters, but cannot manage keys or memberships
+  TeachingAssistant,
+  // read only, eg: parents and counselors
+  Observer,
 }
 
 impl TryFrom<u8> for CourseMembershipKind {
@@ -54,6 +106,10 @@ impl TryFrom<u8> for CourseMembershipKind {
       x if x == CourseMembershipKind::Student as u8 => Ok(CourseMembershipKind::Student),
       x if x == CourseMembershipKind::Instructor as u8 => Ok(CourseMembershipKind::Instructor),
       x if x == CourseMembershipKind::Cancel as u8 => Ok(CourseMembershipKind::Cancel),
+      x if x == CourseMembershipKind::TeachingAssistant as u8 => {
+        Ok(CourseMembershipKind::TeachingAssistant)
+      }
+      x if x == CourseMembershipKind::Observer as u8 => Ok(CourseMembershipKind::Observer),
       x => Err(x),
     }
   }
@@ -99,11 +155,74 @@ impl TryFrom<u8> for IrregularityKind {
   }
 }
 
+// what a user may do within a school or course
+// derived from adminships and course memberships, never stored
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, AsRefStr)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Capability {
+  SchoolEdit,
+  SchoolDurationManage,
+  SchoolKeyManage,
+  AdminshipManage,
+  LocationManage,
+  WebhookManage,
+  DeviceManage,
+  CourseCreate,
+  CourseEdit,
+  CourseKeyManage,
+  CourseMembershipManage,
+  SessionManage,
+  AvailabilityManage,
+  SessionRequestCreate,
+  SessionRequestRespond,
+  CommitmentCreateSelf,
+  CommitmentManage,
+  EncounterCreate,
+  StayManage,
+  CourseView,
+  AttendanceView,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, AsRefStr)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WebhookEventKind {
+  CommitmentNew,
+  SessionRequestResponseNew,
+  StayClose,
+  WaitlistEntryPromoted,
+}
+
+impl TryFrom<u8> for WebhookEventKind {
+  type Error = u8;
+  fn try_from(val: u8) -> Result<WebhookEventKind, u8> {
+    match val {
+      x if x == WebhookEventKind::CommitmentNew as u8 => Ok(WebhookEventKind::CommitmentNew),
+      x if x == WebhookEventKind::SessionRequestResponseNew as u8 => {
+        Ok(WebhookEventKind::SessionRequestResponseNew)
+      }
+      x if x == WebhookEventKind::StayClose as u8 => Ok(WebhookEventKind::StayClose),
+      x if x == WebhookEventKind::WaitlistEntryPromoted as u8 => {
+        Ok(WebhookEventKind::WaitlistEntryPromoted)
+      }
+      x => Err(x),
+    }
+  }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionNewProps {
   pub subscription_kind: SubscriptionKind,
-  pub api_key: String,
+  // Free if omitted
+  #[serde(default)]
+  pub subscription_plan: SubscriptionPlan,
+  // if true, a new period starts when the current one ends, false if omitted
+  #[serde(default)]
+  pub auto_renew: bool,
+  // if a request with this key was already processed, the original result is returned
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -112,7 +231,9 @@ pub struct SchoolNewProps {
   pub name: String,
   pub description: String,
   pub whole: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -122,7 +243,9 @@ pub struct SchoolDataNewProps {
   pub name: String,
   pub description: String,
   pub active: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -132,7 +255,9 @@ pub struct SchoolDurationNewProps {
   pub day: i64,
   pub minute_start: i64,
   pub minute_end: i64,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -143,24 +268,33 @@ pub struct SchoolDurationDataNewProps {
   pub minute_start: i64,
   pub minute_end: i64,
   pub active: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SchoolKeyNewProps {
   pub school_id: i64,
+  // any kind but Cancel, Admin if omitted
+  #[serde(default = "legacy_adminship_kind")]
+  pub adminship_kind: AdminshipKind,
   pub start_time: i64,
   pub end_time: i64,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SchoolKeyDataNewProps {
-  pub school_key_key: String,
+  pub school_key_key: Secret,
   pub active: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -168,14 +302,18 @@ pub struct SchoolKeyDataNewProps {
 pub struct AdminshipNewCancelProps {
   pub user_id: i64,
   pub school_id: i64,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminshipNewKeyProps {
-  pub school_key_key: String,
-  pub api_key: String,
+  pub school_key_key: Secret,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -185,7 +323,9 @@ pub struct LocationNewProps {
   pub name: String,
   pub address: String,
   pub phone: String,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -196,7 +336,9 @@ pub struct LocationDataNewProps {
   pub address: String,
   pub phone: String,
   pub active: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -207,7 +349,9 @@ pub struct CourseNewProps {
   pub name: String,
   pub description: String,
   pub homeroom: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -219,26 +363,33 @@ pub struct CourseDataNewProps {
   pub description: String,
   pub homeroom: bool,
   pub active: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseKeyNewProps {
   pub course_id: i64,
+  // any kind but Cancel
   pub course_membership_kind: CourseMembershipKind,
   pub max_uses: i64,
   pub start_time: i64,
   pub end_time: i64,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseKeyDataNewProps {
-  pub course_key_key: String,
+  pub course_key_key: Secret,
   pub active: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -246,14 +397,18 @@ pub struct CourseKeyDataNewProps {
 pub struct CourseMembershipNewCancelProps {
   pub user_id: i64,
   pub course_id: i64,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseMembershipNewKeyProps {
-  pub course_key_key: String,
-  pub api_key: String,
+  pub course_key_key: Secret,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -266,7 +421,11 @@ pub struct SessionNewProps {
   // produces a commitment with all of these
   // more can always be added later
   pub attendee_user_ids: Vec<i64>,
-  pub api_key: String,
+  // None means unlimited
+  pub max_attendees: Option<i64>,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -277,7 +436,37 @@ pub struct SessionDataNewProps {
   pub active: bool,
   pub start_time: i64,
   pub end_time: i64,
-  pub api_key: String,
+  pub max_attendees: Option<i64>,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// a window in which the creator (an instructor) may be booked
+// split into consecutive slots of slot_duration
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityNewProps {
+  pub course_id: i64,
+  pub start_time: i64,
+  pub end_time: i64,
+  pub slot_duration: i64,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityDataNewProps {
+  pub availability_id: i64,
+  pub start_time: i64,
+  pub end_time: i64,
+  pub slot_duration: i64,
+  pub active: bool,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -287,7 +476,9 @@ pub struct SessionRequestNewProps {
   pub message: String,
   pub start_time: i64,
   pub end_time: i64,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -296,7 +487,22 @@ pub struct SessionRequestResponseNewProps {
   pub session_request_id: i64,
   pub message: String,
   pub session_id: Option<i64>,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// claims a free slot, creating a session with the slot's times and a commitment to it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitmentNewSlotProps {
+  pub availability_id: i64,
+  pub start_time: i64,
+  // the name of the created session
+  pub name: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -304,16 +510,110 @@ pub struct SessionRequestResponseNewProps {
 pub struct CommitmentNewProps {
   pub attendee_user_ids: Vec<i64>,
   pub session_id: i64,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+  pub active: bool,
+}
+
+// joins (or with active false, leaves) the waitlist of a full session
+// entries are promoted into commitments in the order they joined
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistEntryNewProps {
+  pub attendee_user_ids: Vec<i64>,
+  pub session_id: i64,
   pub active: bool,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
+// bounds on occurrence_time, in milliseconds relative to the server's creation_time
+// later than creation_time + ENCOUNTER_OCCURRENCE_TIME_MAX_FUTURE is EncounterOccurrenceTimeFuture
+// earlier than creation_time - ENCOUNTER_OCCURRENCE_TIME_MAX_AGE is EncounterOccurrenceTimeTooOld
+pub const ENCOUNTER_OCCURRENCE_TIME_MAX_FUTURE: i64 = 60 * 1000;
+pub const ENCOUNTER_OCCURRENCE_TIME_MAX_AGE: i64 = 7 * 24 * 60 * 60 * 1000;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EncounterNewProps {
   pub attendee_user_id: i64,
   pub location_id: i64,
-  pub api_key: String,
+  // when the scan actually happened, for queued or batched hardware
+  // if None, the server uses the creation_time, otherwise it must be within the bounds above
+  pub occurrence_time: Option<i64>,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// the secret used to sign deliveries is generated by the server, and only returned on creation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookNewProps {
+  pub school_id: i64,
+  pub url: String,
+  pub webhook_event_kinds: Vec<WebhookEventKind>,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDataNewProps {
+  pub webhook_id: i64,
+  pub active: bool,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// a credential for a kiosk or card reader, scoped to some of a school's locations
+// the returned device key may be used as the api_key of EncounterNewProps and EncounterBulkNewProps
+// and, if stay_view is set, of StayDataViewProps (limited to today)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceNewProps {
+  pub school_id: i64,
+  pub name: String,
+  pub location_ids: Vec<i64>,
+  pub stay_view: bool,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// deactivating a device stops its key from being accepted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceDataNewProps {
+  pub device_id: i64,
+  pub active: bool,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// a single scan inside of an EncounterBulkNewProps
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncounterBulkNewItem {
+  pub attendee_user_id: i64,
+  pub location_id: i64,
+  pub occurrence_time: Option<i64>,
+}
+
+// many encounters under one api key, for gateways that batch scans
+// each item succeeds or fails independently
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncounterBulkNewProps {
+  pub encounters: Vec<EncounterBulkNewItem>,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -321,7 +621,10 @@ pub struct EncounterNewProps {
 pub struct EncounterNewAttendsProps {
   pub attendee_user_id: i64,
   pub location_id: i64,
-  pub api_key: String,
+  pub occurrence_time: Option<i64>,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -333,7 +636,9 @@ pub struct StayNewProps {
   pub fst_encounter_id: Option<i64>,
   pub snd_time: Option<i64>,
   pub snd_encounter_id: Option<i64>,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -345,7 +650,81 @@ pub struct StayDataNewProps {
   pub snd_time: Option<i64>,
   pub snd_encounter_id: Option<i64>,
   pub active: bool,
-  pub api_key: String,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// any New props, eg: as an item of a BatchNewProps
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "props", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BatchNewItemProps {
+  SubscriptionNew(SubscriptionNewProps),
+  SchoolNew(SchoolNewProps),
+  SchoolDataNew(SchoolDataNewProps),
+  SchoolDurationNew(SchoolDurationNewProps),
+  SchoolDurationDataNew(SchoolDurationDataNewProps),
+  SchoolKeyNew(SchoolKeyNewProps),
+  SchoolKeyDataNew(SchoolKeyDataNewProps),
+  AdminshipNewCancel(AdminshipNewCancelProps),
+  AdminshipNewKey(AdminshipNewKeyProps),
+  LocationNew(LocationNewProps),
+  LocationDataNew(LocationDataNewProps),
+  CourseNew(CourseNewProps),
+  CourseDataNew(CourseDataNewProps),
+  CourseKeyNew(CourseKeyNewProps),
+  CourseKeyDataNew(CourseKeyDataNewProps),
+  CourseMembershipNewCancel(CourseMembershipNewCancelProps),
+  CourseMembershipNewKey(CourseMembershipNewKeyProps),
+  SessionNew(SessionNewProps),
+  SessionDataNew(SessionDataNewProps),
+  SessionRequestNew(SessionRequestNewProps),
+  SessionRequestResponseNew(SessionRequestResponseNewProps),
+  CommitmentNew(CommitmentNewProps),
+  CommitmentNewSlot(CommitmentNewSlotProps),
+  AvailabilityNew(AvailabilityNewProps),
+  AvailabilityDataNew(AvailabilityDataNewProps),
+  WaitlistEntryNew(WaitlistEntryNewProps),
+  EncounterNew(EncounterNewProps),
+  EncounterNewAttends(EncounterNewAttendsProps),
+  StayNew(StayNewProps),
+  StayDataNew(StayDataNewProps),
+  WebhookNew(WebhookNewProps),
+  WebhookDataNew(WebhookDataNewProps),
+  DeviceNew(DeviceNewProps),
+  DeviceDataNew(DeviceDataNewProps),
+}
+
+// replaces an id field of an item with the id produced by an earlier item
+// field is the camelCase name of the field (eg: courseId)
+// the produced id is that of the entity, not the data record (eg: the course_id of a CourseData)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchNewReference {
+  pub field: String,
+  pub item_index: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchNewItem {
+  pub props: BatchNewItemProps,
+  pub references: Vec<BatchNewReference>,
+}
+
+// executes all items in order inside of a single transaction
+// if any item fails, nothing is committed and that error is returned
+// every item is authorized with the batch's credential (the api_key below if not empty, otherwise the
+// Authorization header), as if it were sent alone under that credential
+// the idempotency_key below covers the whole batch
+// the api_key and idempotency_key of each item are ignored
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchNewProps {
+  pub items: Vec<BatchNewItem>,
+  pub idempotency_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -356,8 +735,22 @@ pub struct SubscriptionViewProps {
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub subscription_kind: Option<Vec<SubscriptionKind>>,
+  pub subscription_plan: Option<Vec<SubscriptionPlan>>,
+  pub min_remaining_uses: Option<i64>,
+  pub max_remaining_uses: Option<i64>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// consumption of the subscription's current period
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUsageViewProps {
+  // if None, the most recent subscription of the user owning the api key
+  pub subscription_id: Option<i64>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -368,7 +761,8 @@ pub struct SchoolViewProps {
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub whole: Option<bool>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -385,7 +779,8 @@ pub struct SchoolDataViewProps {
   pub partial_description: Option<String>,
   pub active: Option<bool>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -396,7 +791,8 @@ pub struct SchoolDurationViewProps {
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub school_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -415,22 +811,25 @@ pub struct SchoolDurationDataViewProps {
   pub active: Option<bool>,
   pub only_recent: bool,
   pub school_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SchoolKeyViewProps {
-  pub school_key_key: Option<Vec<String>>,
+  pub school_key_key: Option<Vec<Secret>>,
   pub min_creation_time: Option<i64>,
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub school_id: Option<Vec<i64>>,
+  pub adminship_kind: Option<Vec<AdminshipKind>>,
   pub min_start_time: Option<i64>,
   pub max_start_time: Option<i64>,
   pub min_end_time: Option<i64>,
   pub max_end_time: Option<i64>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -440,15 +839,26 @@ pub struct SchoolKeyDataViewProps {
   pub min_creation_time: Option<i64>,
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
-  pub school_key_key: Option<Vec<String>>,
+  pub school_key_key: Option<Vec<Secret>>,
   pub active: Option<bool>,
   pub school_id: Option<Vec<i64>>,
+  pub adminship_kind: Option<Vec<AdminshipKind>>,
   pub min_start_time: Option<i64>,
   pub max_start_time: Option<i64>,
   pub min_end_time: Option<i64>,
   pub max_end_time: Option<i64>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// what redeeming the key would grant, without using it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchoolKeyPreviewProps {
+  pub school_key_key: Secret,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -462,9 +872,38 @@ pub struct AdminshipViewProps {
   pub school_id: Option<Vec<i64>>,
   pub adminship_kind: Option<Vec<AdminshipKind>>,
   pub adminship_has_source: Option<bool>,
-  pub school_key_key: Option<Vec<String>>,
+  pub school_key_key: Option<Vec<Secret>>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceViewProps {
+  pub device_id: Option<Vec<i64>>,
+  pub min_creation_time: Option<i64>,
+  pub max_creation_time: Option<i64>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub school_id: Option<Vec<i64>>,
+  pub location_id: Option<Vec<i64>>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceDataViewProps {
+  pub device_data_id: Option<Vec<i64>>,
+  pub min_creation_time: Option<i64>,
+  pub max_creation_time: Option<i64>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub device_id: Option<Vec<i64>>,
+  pub active: Option<bool>,
+  pub school_id: Option<Vec<i64>>,
+  pub only_recent: bool,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -475,7 +914,8 @@ pub struct LocationViewProps {
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub school_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -494,7 +934,8 @@ pub struct LocationDataViewProps {
   pub active: Option<bool>,
   pub only_recent: bool,
   pub school_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 
@@ -506,7 +947,8 @@ pub struct CourseViewProps {
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub school_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -526,24 +968,28 @@ pub struct CourseDataViewProps {
   pub active: Option<bool>,
   pub only_recent: bool,
   pub school_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseKeyViewProps {
-  pub course_key_key: Option<Vec<String>>,
+  pub course_key_key: Option<Vec<Secret>>,
   pub min_creation_time: Option<i64>,
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub course_id: Option<Vec<i64>>,
   pub max_uses: Option<Vec<i64>>,
+  pub min_remaining_uses: Option<i64>,
+  pub max_remaining_uses: Option<i64>,
   pub course_membership_kind: Option<Vec<CourseMembershipKind>>,
   pub min_start_time: Option<i64>,
   pub max_start_time: Option<i64>,
   pub min_end_time: Option<i64>,
   pub max_end_time: Option<i64>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -553,17 +999,29 @@ pub struct CourseKeyDataViewProps {
   pub min_creation_time: Option<i64>,
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
-  pub course_key_key: Option<Vec<String>>,
+  pub course_key_key: Option<Vec<Secret>>,
   pub active: Option<bool>,
   pub course_id: Option<Vec<i64>>,
   pub max_uses: Option<Vec<i64>>,
+  pub min_remaining_uses: Option<i64>,
+  pub max_remaining_uses: Option<i64>,
   pub course_membership_kind: Option<Vec<CourseMembershipKind>>,
   pub min_start_time: Option<i64>,
   pub max_start_time: Option<i64>,
   pub min_end_time: Option<i64>,
   pub max_end_time: Option<i64>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// what redeeming the key would grant, without using it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseKeyPreviewProps {
+  pub course_key_key: Secret,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -577,9 +1035,10 @@ pub struct CourseMembershipViewProps {
   pub course_id: Option<Vec<i64>>,
   pub course_membership_kind: Option<Vec<CourseMembershipKind>>,
   pub course_membership_from_key: Option<bool>,
-  pub course_key_key: Option<Vec<String>>,
+  pub course_key_key: Option<Vec<Secret>>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -590,7 +1049,8 @@ pub struct SessionViewProps {
   pub max_creation_time: Option<i64>,
   pub creator_user_id: Option<Vec<i64>>,
   pub course_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -609,8 +1069,79 @@ pub struct SessionDataViewProps {
   pub max_end_time: Option<i64>,
   pub active: Option<bool>,
   pub course_id: Option<Vec<i64>>,
+  // whether fewer active commitments than max_attendees exist
+  pub has_capacity: Option<bool>,
+  pub only_recent: bool,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityViewProps {
+  pub availability_id: Option<Vec<i64>>,
+  pub min_creation_time: Option<i64>,
+  pub max_creation_time: Option<i64>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub course_id: Option<Vec<i64>>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityDataViewProps {
+  pub availability_data_id: Option<Vec<i64>>,
+  pub min_creation_time: Option<i64>,
+  pub max_creation_time: Option<i64>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub availability_id: Option<Vec<i64>>,
+  pub min_start_time: Option<i64>,
+  pub max_start_time: Option<i64>,
+  pub min_end_time: Option<i64>,
+  pub max_end_time: Option<i64>,
+  pub active: Option<bool>,
+  pub course_id: Option<Vec<i64>>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// unclaimed slots of active availabilities
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilitySlotViewProps {
+  pub availability_id: Option<Vec<i64>>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub course_id: Option<Vec<i64>>,
+  pub min_start_time: Option<i64>,
+  pub max_end_time: Option<i64>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// limits on FreeWindowViewProps, outside of which the view fails with FreeWindowRangeInvalid
+// step must be at least FREE_WINDOW_MIN_STEP milliseconds
+// max_end_time - min_start_time must be at most FREE_WINDOW_MAX_RANGE milliseconds
+pub const FREE_WINDOW_MIN_STEP: i64 = 5 * 60 * 1000;
+pub const FREE_WINDOW_MAX_RANGE: i64 = 31 * 24 * 60 * 60 * 1000;
+
+// windows of duration in which all users are free and the course's school is open
+// ranked by closeness to preferred_start_time, or earliest first
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeWindowViewProps {
+  pub user_id: Vec<i64>,
+  pub course_id: i64,
+  pub min_start_time: i64,
+  pub max_end_time: i64,
+  pub duration: i64,
+  // windows start at multiples of step after min_start_time
+  pub step: i64,
+  pub preferred_start_time: Option<i64>,
+  pub count: Option<i64>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -628,7 +1159,19 @@ pub struct SessionRequestViewProps {
   pub min_end_time: Option<i64>,
   pub max_end_time: Option<i64>,
   pub responded: Option<bool>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// streams session requests and their responses as they are created
+// filtered like SessionRequestViewProps
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRequestSubscribeProps {
+  pub creator_user_id: Option<Vec<i64>>,
+  pub course_id: Option<Vec<i64>>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -649,7 +1192,8 @@ pub struct SessionRequestResponseViewProps {
   pub min_end_time: Option<i64>,
   pub max_end_time: Option<i64>,
   pub session_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -669,7 +1213,25 @@ pub struct CommitmentViewProps {
   pub active: Option<bool>,
   pub from_request_response: Option<bool>,
   pub only_recent: bool,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistEntryViewProps {
+  pub waitlist_entry_id: Option<Vec<i64>>,
+  pub min_creation_time: Option<i64>,
+  pub max_creation_time: Option<i64>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub attendee_user_id: Option<Vec<i64>>,
+  pub session_id: Option<Vec<i64>>,
+  pub course_id: Option<Vec<i64>>,
+  pub active: Option<bool>,
+  pub promoted: Option<bool>,
+  pub only_recent: bool,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -681,7 +1243,21 @@ pub struct EncounterViewProps {
   pub creator_user_id: Option<Vec<i64>>,
   pub attendee_user_id: Option<Vec<i64>>,
   pub location_id: Option<Vec<i64>>,
-  pub api_key: String,
+  pub min_occurrence_time: Option<i64>,
+  pub max_occurrence_time: Option<i64>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// streams encounters as they are created, filtered like EncounterViewProps
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncounterSubscribeProps {
+  pub creator_user_id: Option<Vec<i64>>,
+  pub attendee_user_id: Option<Vec<i64>>,
+  pub location_id: Option<Vec<i64>>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -693,7 +1269,8 @@ pub struct StayViewProps {
   pub creator_user_id: Option<Vec<i64>>,
   pub attendee_user_id: Option<Vec<i64>>,
   pub location_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -714,6 +1291,110 @@ pub struct StayDataViewProps {
   pub only_recent: bool,
   pub attendee_user_id: Option<Vec<i64>>,
   pub location_id: Option<Vec<i64>>,
-  pub api_key: String,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookViewProps {
+  pub webhook_id: Option<Vec<i64>>,
+  pub min_creation_time: Option<i64>,
+  pub max_creation_time: Option<i64>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub school_id: Option<Vec<i64>>,
+  pub webhook_event_kind: Option<Vec<WebhookEventKind>>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDataViewProps {
+  pub webhook_data_id: Option<Vec<i64>>,
+  pub min_creation_time: Option<i64>,
+  pub max_creation_time: Option<i64>,
+  pub creator_user_id: Option<Vec<i64>>,
+  pub webhook_id: Option<Vec<i64>>,
+  pub active: Option<bool>,
+  pub school_id: Option<Vec<i64>>,
+  pub only_recent: bool,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// the effective capabilities of a user in each school and course they belong to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilitiesViewProps {
+  // if None, the user owning the api key
+  pub user_id: Option<i64>,
+  pub school_id: Option<Vec<i64>>,
+  pub course_id: Option<Vec<i64>>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+// every record of every type created after a watermark, in commit order
+// the watermark is a sequence number, not a creation_time: the server assigns each record the next
+// sequence number when its transaction commits, so a record committed after a page was served
+// always comes after that page's watermark, even if its creation_time is earlier
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeViewProps {
+  // exclusive, 0 for everything
+  pub min_sequence: i64,
+  pub count: Option<i64>,
+  #[serde(default, skip_serializing_if = "Secret::is_empty")]
+  pub api_key: Secret,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn empty_api_key_is_left_out_of_the_body() {
+    let mut props = SchoolViewProps {
+      school_id: Some(vec![1]),
+      min_creation_time: None,
+      max_creation_time: None,
+      creator_user_id: None,
+      whole: None,
+      api_key: Secret::default(),
+    };
+    let body = serde_json::to_value(&props).unwrap();
+    assert!(body.get("apiKey").is_none());
+    let props2: SchoolViewProps = serde_json::from_value(body).unwrap();
+    assert!(props2.api_key.is_empty());
+
+    props.api_key = Secret::from("key");
+    assert_eq!(
+      serde_json::to_value(&props).unwrap()["apiKey"],
+      json!("key")
+    );
+  }
+
+  #[test]
+  fn bodies_without_newer_fields() {
+    let props: SubscriptionNewProps = serde_json::from_value(json!({
+      "subscriptionKind": "VALID",
+      "idempotencyKey": null,
+      "apiKey": "key",
+    }))
+    .unwrap();
+    assert!(matches!(props.subscription_plan, SubscriptionPlan::Free));
+    assert!(!props.auto_renew);
+
+    let props: SchoolKeyNewProps = serde_json::from_value(json!({
+      "schoolId": 1,
+      "startTime": 0,
+      "endTime": 1,
+      "idempotencyKey": null,
+      "apiKey": "key",
+    }))
+    .unwrap();
+    assert!(matches!(props.adminship_kind, AdminshipKind::Admin));
+  }
+}
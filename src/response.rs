@@ -1,4 +1,8 @@
-use super::request::{AdminshipKind, CourseMembershipKind, EncounterKind, SubscriptionKind};
+use super::request::{
+  legacy_adminship_kind, legacy_subscription_plan, AdminshipKind, Capability,
+  CourseMembershipKind, EncounterKind, SubscriptionKind, SubscriptionPlan, WebhookEventKind,
+};
+use super::secret::Secret;
 use either::Either;
 use serde::{Deserialize, Serialize};
 use strum::AsRefStr;
@@ -29,13 +33,17 @@ pub enum InnexgoHoursError {
   SubscriptionExpired,
   SubscriptionUnauthorized,
   SubscriptionLimited,
+  SubscriptionSeatsLimited,
 
   SchoolNonexistent,
   SchoolArchived,
 
+  KeyMalformed,
+
   SchoolKeyNonexistent,
   SchoolKeyExpired,
   SchoolKeyUsed,
+  SchoolKeyKindInvalid,
 
   SchoolDurationNonexistent,
   SchoolDurationDayInvalid,
@@ -48,8 +56,15 @@ pub enum InnexgoHoursError {
   SessionRequestResponseExistent,
   SessionRequestResponseCannotCancelStudent,
 
+  AvailabilityNonexistent,
+  AvailabilitySlotDurationInvalid,
+  AvailabilitySlotNonexistent,
+  AvailabilitySlotTaken,
+
   SessionNotRelevant,
   SessionNonexistent,
+  SessionFull,
+  SessionMaxAttendeesInvalid,
 
   CommitmentExistent,
   CommitmentNonexistent,
@@ -57,13 +72,21 @@ pub enum InnexgoHoursError {
   CommitmentCannotCreateHiddenStudent,
   CommitmentCannotCreateUncancellableStudent,
 
+  WaitlistEntryExistent,
+  WaitlistEntryNonexistent,
+  WaitlistEntryCannotCreateForOthersStudent,
+  WaitlistSessionNotFull,
+
   EncounterNonexistent,
+  EncounterOccurrenceTimeFuture,
+  EncounterOccurrenceTimeTooOld,
 
   StayNonexistent,
   StayProvidedNoTime,
   StayProvidedDoubleTime,
   StayEncounterWrongUser,
   StayEncounterWrongLocation,
+  StayEncounterWrongOrder,
 
   CourseNonexistent,
   CourseArchived,
@@ -71,16 +94,39 @@ pub enum InnexgoHoursError {
   CourseKeyNonexistent,
   CourseKeyExpired,
   CourseKeyUsed,
+  CourseKeyKindInvalid,
 
   CourseMembershipNonexistent,
   CourseMembershipCannotLeaveEmpty,
 
+  DeviceNonexistent,
+  DeviceArchived,
+  DeviceUnauthorized,
+  DeviceLocationUnauthorized,
+
   LocationNonexistent,
   LocationArchived,
 
+  ConflictAttendeeCommitment,
+  ConflictInstructorSession,
+  ConflictLocationSession,
+  ConflictSchoolClosed,
+
+  FreeWindowRangeInvalid,
+
   NegativeDuration,
   CannotAlterPast,
 
+  IdempotencyKeyReused,
+
+  WebhookNonexistent,
+  WebhookUrlInvalid,
+  WebhookSignatureInvalid,
+  WebhookSignatureExpired,
+
+  BatchEmpty,
+  BatchReferenceInvalid,
+
   VerificationChallengeNonexistent,
   VerificationChallengeTimedOut,
   PasswordResetNonexistent,
@@ -104,7 +150,44 @@ pub struct Subscription {
   pub creation_time: i64,
   pub creator_user_id: i64,
   pub subscription_kind: SubscriptionKind,
+  // Legacy for subscriptions created before plans were added
+  // the plan's seats and period are given by subscription::plan_limits
+  #[serde(default = "legacy_subscription_plan")]
+  pub subscription_plan: SubscriptionPlan,
+  // a use is a school created under the subscription
+  // max_uses is set from the plan when the subscription is created, and is the limit on uses
   pub max_uses: i64,
+  pub uses: i64,
+  pub remaining_uses: i64,
+  // the current period, after which the subscription is expired unless renewed
+  // subscriptions created before periods were added never expire
+  #[serde(default)]
+  pub period_start_time: i64,
+  #[serde(default = "legacy_period_end_time")]
+  pub period_end_time: i64,
+  #[serde(default)]
+  pub auto_renew: bool,
+}
+
+fn legacy_period_end_time() -> i64 {
+  i64::MAX
+}
+
+// one school counted against a subscription
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUsageEntry {
+  pub school: School,
+  pub seats: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUsage {
+  pub subscription: Subscription,
+  pub entries: Vec<SubscriptionUsageEntry>,
+  // a seat is an active course membership in one of the subscription's schools
+  pub seats: i64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -153,10 +236,13 @@ pub struct SchoolDurationData {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SchoolKey {
-  pub school_key_key: String,
+  pub school_key_key: Secret,
   pub creation_time: i64,
   pub creator_user_id: i64,
   pub school: School,
+  // Admin for keys created before kinds were added
+  #[serde(default = "legacy_adminship_kind")]
+  pub adminship_kind: AdminshipKind,
   pub start_time: i64,
   pub end_time: i64,
 }
@@ -171,6 +257,20 @@ pub struct SchoolKeyData {
   pub active: bool,
 }
 
+// public summary of a school key, see SchoolKeyPreviewProps
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchoolKeyPreview {
+  pub school_data: SchoolData,
+  pub adminship_kind: AdminshipKind,
+  pub start_time: i64,
+  pub end_time: i64,
+  // redeeming the key fails if it is expired, used up, or no longer active
+  pub expired: bool,
+  pub used: bool,
+  pub active: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Adminship {
@@ -205,6 +305,36 @@ pub struct LocationData {
   pub active: bool,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Device {
+  pub device_id: i64,
+  pub creation_time: i64,
+  pub creator_user_id: i64,
+  pub school: School,
+  pub name: String,
+  pub locations: Vec<Location>,
+  pub stay_view: bool,
+}
+
+// only returned when the device is created, the key cannot be retrieved later
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCredential {
+  pub device: Device,
+  pub device_key: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceData {
+  pub device_data_id: i64,
+  pub creation_time: i64,
+  pub creator_user_id: i64,
+  pub device: Device,
+  pub active: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Course {
@@ -231,11 +361,14 @@ pub struct CourseData {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseKey {
-  pub course_key_key: String,
+  pub course_key_key: Secret,
   pub creation_time: i64,
   pub creator_user_id: i64,
   pub course: Course,
   pub max_uses: i64,
+  // memberships created with this key
+  pub uses: i64,
+  pub remaining_uses: i64,
   pub course_membership_kind: CourseMembershipKind,
   pub start_time: i64,
   pub end_time: i64,
@@ -251,6 +384,22 @@ pub struct CourseKeyData {
   pub active: bool,
 }
 
+// public summary of a course key, see CourseKeyPreviewProps
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseKeyPreview {
+  pub course_data: CourseData,
+  pub school_data: SchoolData,
+  pub course_membership_kind: CourseMembershipKind,
+  pub start_time: i64,
+  pub end_time: i64,
+  pub remaining_uses: i64,
+  // redeeming the key fails if it is expired, used up, or no longer active
+  pub expired: bool,
+  pub used: bool,
+  pub active: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseMembership {
@@ -282,9 +431,51 @@ pub struct SessionData {
   pub name: String,
   pub start_time: i64,
   pub end_time: i64,
+  pub max_attendees: Option<i64>,
+  // number of currently active commitments to the session
+  pub commitment_count: i64,
+  // number of active, unpromoted waitlist entries
+  pub waitlist_count: i64,
+  pub active: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Availability {
+  pub availability_id: i64,
+  pub creation_time: i64,
+  pub creator_user_id: i64,
+  pub course: Course,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityData {
+  pub availability_data_id: i64,
+  pub creation_time: i64,
+  pub creator_user_id: i64,
+  pub availability: Availability,
+  pub start_time: i64,
+  pub end_time: i64,
+  pub slot_duration: i64,
   pub active: bool,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilitySlot {
+  pub availability: Availability,
+  pub start_time: i64,
+  pub end_time: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeWindow {
+  pub start_time: i64,
+  pub end_time: i64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionRequest {
@@ -318,6 +509,21 @@ pub struct Commitment {
   pub active: bool,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistEntry {
+  pub waitlist_entry_id: i64,
+  pub creation_time: i64,
+  pub creator_user_id: i64,
+  pub attendee_user_id: i64,
+  pub session: Session,
+  // 1 is next to be promoted, None if inactive or promoted
+  pub position: Option<i64>,
+  // the commitment this entry was promoted into
+  pub commitment: Option<Commitment>,
+  pub active: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Encounter {
@@ -327,6 +533,11 @@ pub struct Encounter {
   pub location_id: i64,
   pub attendee_user_id: i64,
   pub encounter_kind: EncounterKind,
+  // the device which recorded the encounter, if any
+  pub device_id: Option<i64>,
+  // when the encounter happened, may be earlier than creation_time
+  // stays are paired and ordered by this, not creation_time
+  pub occurrence_time: i64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -352,3 +563,184 @@ pub struct StayData {
   pub snd: Either<Encounter, i64>,
   pub active: bool,
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+  pub webhook_id: i64,
+  pub creation_time: i64,
+  pub creator_user_id: i64,
+  pub school: School,
+  pub url: String,
+  pub webhook_event_kinds: Vec<WebhookEventKind>,
+}
+
+// only returned when the webhook is created, the secret cannot be retrieved later
+// the secret is used to sign each delivery, see the webhook module
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookCredential {
+  pub webhook: Webhook,
+  pub secret: Secret,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookData {
+  pub webhook_data_id: i64,
+  pub creation_time: i64,
+  pub creator_user_id: i64,
+  pub webhook: Webhook,
+  pub active: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "record", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WebhookEvent {
+  CommitmentNew(Commitment),
+  SessionRequestResponseNew(SessionRequestResponse),
+  // the stay data which set snd
+  StayClose(StayData),
+  // the entry, with the commitment it was promoted into
+  WaitlistEntryPromoted(WaitlistEntry),
+}
+
+// the body POSTed to a webhook's url
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+  pub webhook_id: i64,
+  pub creation_time: i64,
+  pub event: WebhookEvent,
+}
+
+// the result of each item in an EncounterBulkNewProps, in the same order
+// each item succeeds or fails independently
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "response", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EncounterBulkNewItemResponse {
+  Encounter(Encounter),
+  Error(InnexgoHoursError),
+}
+
+// the result of each item in a BatchNewProps, in the same order
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "response", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BatchNewItemResponse {
+  SubscriptionNew(Subscription),
+  SchoolNew(SchoolData),
+  SchoolDataNew(SchoolData),
+  SchoolDurationNew(SchoolDurationData),
+  SchoolDurationDataNew(SchoolDurationData),
+  SchoolKeyNew(SchoolKeyData),
+  SchoolKeyDataNew(SchoolKeyData),
+  AdminshipNewCancel(Adminship),
+  AdminshipNewKey(Adminship),
+  LocationNew(LocationData),
+  LocationDataNew(LocationData),
+  CourseNew(CourseData),
+  CourseDataNew(CourseData),
+  CourseKeyNew(CourseKeyData),
+  CourseKeyDataNew(CourseKeyData),
+  CourseMembershipNewCancel(CourseMembership),
+  CourseMembershipNewKey(CourseMembership),
+  SessionNew(SessionData),
+  SessionDataNew(SessionData),
+  SessionRequestNew(SessionRequest),
+  SessionRequestResponseNew(SessionRequestResponse),
+  CommitmentNew(Vec<Commitment>),
+  CommitmentNewSlot(Commitment),
+  AvailabilityNew(AvailabilityData),
+  AvailabilityDataNew(AvailabilityData),
+  WaitlistEntryNew(Vec<WaitlistEntry>),
+  EncounterNew(Encounter),
+  EncounterNewAttends(Encounter),
+  StayNew(StayData),
+  StayDataNew(StayData),
+  WebhookNew(WebhookCredential),
+  WebhookDataNew(WebhookData),
+  DeviceNew(DeviceCredential),
+  DeviceDataNew(DeviceData),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "record", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Change {
+  Subscription(Subscription),
+  School(School),
+  SchoolData(SchoolData),
+  SchoolDuration(SchoolDuration),
+  SchoolDurationData(SchoolDurationData),
+  SchoolKey(SchoolKey),
+  SchoolKeyData(SchoolKeyData),
+  Adminship(Adminship),
+  Location(Location),
+  LocationData(LocationData),
+  Course(Course),
+  CourseData(CourseData),
+  CourseKey(CourseKey),
+  CourseKeyData(CourseKeyData),
+  CourseMembership(CourseMembership),
+  Session(Session),
+  SessionData(SessionData),
+  Availability(Availability),
+  AvailabilityData(AvailabilityData),
+  SessionRequest(SessionRequest),
+  SessionRequestResponse(SessionRequestResponse),
+  Commitment(Commitment),
+  WaitlistEntry(WaitlistEntry),
+  Encounter(Encounter),
+  Stay(Stay),
+  StayData(StayData),
+  Webhook(Webhook),
+  WebhookData(WebhookData),
+  Device(Device),
+  DeviceData(DeviceData),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePage {
+  pub changes: Vec<Change>,
+  // the sequence number of the last change, or the requested min_sequence if there were none
+  pub watermark: i64,
+  pub more: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "record", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EncounterEvent {
+  EncounterNew(Encounter),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "record", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SessionRequestEvent {
+  SessionRequestNew(SessionRequest),
+  SessionRequestResponseNew(SessionRequestResponse),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchoolCapabilities {
+  pub school: School,
+  pub adminship_kind: Option<AdminshipKind>,
+  pub capabilities: Vec<Capability>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseCapabilities {
+  pub course: Course,
+  pub course_membership_kind: Option<CourseMembershipKind>,
+  // includes those granted by an adminship of the course's school
+  pub capabilities: Vec<Capability>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+  pub user_id: i64,
+  pub schools: Vec<SchoolCapabilities>,
+  pub courses: Vec<CourseCapabilities>,
+}
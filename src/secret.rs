@@ -0,0 +1,78 @@
+// Strings which must not appear in logs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// serializes exactly like a String, but Debug and Display never print the contents
+#[derive(Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+  pub fn new(secret: impl Into<String>) -> Self {
+    Secret(secret.into())
+  }
+
+  // the actual contents, only for sending or comparing
+  pub fn expose(&self) -> &str {
+    &self.0
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+}
+
+impl From<String> for Secret {
+  fn from(secret: String) -> Self {
+    Secret(secret)
+  }
+}
+
+impl From<&str> for Secret {
+  fn from(secret: &str) -> Self {
+    Secret(String::from(secret))
+  }
+}
+
+impl fmt::Debug for Secret {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Secret(<redacted>)")
+  }
+}
+
+impl fmt::Display for Secret {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("<redacted>")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::request::SchoolKeyDataNewProps;
+
+  fn props() -> SchoolKeyDataNewProps {
+    SchoolKeyDataNewProps {
+      school_key_key: Secret::from("school-key-abcd"),
+      active: false,
+      idempotency_key: None,
+      api_key: Secret::from("api-key-efgh"),
+    }
+  }
+
+  #[test]
+  fn debug_of_props_hides_keys() {
+    let debug = format!("{:?}", props());
+    assert!(!debug.contains("school-key-abcd"));
+    assert!(!debug.contains("api-key-efgh"));
+  }
+
+  #[test]
+  fn json_round_trips_keys() {
+    let json = serde_json::to_string(&props()).unwrap();
+    assert!(json.contains("\"schoolKeyKey\":\"school-key-abcd\""));
+    let props: SchoolKeyDataNewProps = serde_json::from_str(&json).unwrap();
+    assert_eq!(props.school_key_key.expose(), "school-key-abcd");
+    assert_eq!(props.api_key.expose(), "api-key-efgh");
+  }
+}
@@ -0,0 +1,251 @@
+// Plan limits, and when a subscription allows creating schools and seats
+use super::request::{SubscriptionKind, SubscriptionPlan};
+use super::response::{InnexgoHoursError, Subscription, SubscriptionUsage};
+
+const DAY: i64 = 24 * 60 * 60 * 1000;
+
+// the limits of each plan are defined only here
+// the server sets the max_uses (and so remaining_uses) of each new Subscription from these
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlanLimits {
+  // schools that may be created under the subscription
+  pub max_uses: i64,
+  // active course memberships across those schools
+  pub max_seats: i64,
+  // length of a period in milliseconds
+  pub period: i64,
+}
+
+pub fn plan_limits(subscription_plan: &SubscriptionPlan) -> PlanLimits {
+  match subscription_plan {
+    SubscriptionPlan::Free => PlanLimits {
+      max_uses: 1,
+      max_seats: 50,
+      period: 30 * DAY,
+    },
+    SubscriptionPlan::Basic => PlanLimits {
+      max_uses: 1,
+      max_seats: 500,
+      period: 365 * DAY,
+    },
+    SubscriptionPlan::Standard => PlanLimits {
+      max_uses: 5,
+      max_seats: 5000,
+      period: 365 * DAY,
+    },
+    SubscriptionPlan::Enterprise => PlanLimits {
+      max_uses: 100,
+      max_seats: 100000,
+      period: 365 * DAY,
+    },
+    // never given to new subscriptions
+    // legacy subscriptions keep their stored max_uses, had no seat limit, and do not renew
+    SubscriptionPlan::Legacy => PlanLimits {
+      max_uses: 0,
+      max_seats: i64::MAX,
+      period: i64::MAX,
+    },
+  }
+}
+
+// the period containing now, following on from the subscription's current period if it auto renews
+pub fn current_period(subscription: &Subscription, now: i64) -> Option<(i64, i64)> {
+  let start = subscription.period_start_time;
+  let end = subscription.period_end_time;
+  if now < end {
+    return Some((start, end));
+  }
+  if !subscription.auto_renew || end <= start {
+    return None;
+  }
+
+  let length = end - start;
+  let elapsed = (now - start) / length;
+  Some((start + elapsed * length, start + (elapsed + 1) * length))
+}
+
+// fails with SubscriptionExpired if cancelled, or past the end of its period without renewal
+pub fn check_valid(subscription: &Subscription, now: i64) -> Result<(), InnexgoHoursError> {
+  match (
+    &subscription.subscription_kind,
+    current_period(subscription, now),
+  ) {
+    (SubscriptionKind::Valid, Some(_)) => Ok(()),
+    _ => Err(InnexgoHoursError::SubscriptionExpired),
+  }
+}
+
+// whether another school may be created under the subscription
+// uses the stored max_uses, so legacy subscriptions keep their capacity
+pub fn check_school_new(subscription: &Subscription, now: i64) -> Result<(), InnexgoHoursError> {
+  check_valid(subscription, now)?;
+  if subscription.uses >= subscription.max_uses {
+    return Err(InnexgoHoursError::SubscriptionLimited);
+  }
+  Ok(())
+}
+
+// whether another course membership may be created in one of the subscription's schools
+pub fn check_seat_new(
+  subscription_usage: &SubscriptionUsage,
+  now: i64,
+) -> Result<(), InnexgoHoursError> {
+  let subscription = &subscription_usage.subscription;
+  check_valid(subscription, now)?;
+  if subscription_usage.seats >= plan_limits(&subscription.subscription_plan).max_seats {
+    return Err(InnexgoHoursError::SubscriptionSeatsLimited);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const NOW: i64 = 1_700_000_000_000;
+
+  fn subscription(
+    subscription_plan: SubscriptionPlan,
+    uses: i64,
+    auto_renew: bool,
+  ) -> Subscription {
+    let limits = plan_limits(&subscription_plan);
+    Subscription {
+      subscription_id: 1,
+      creation_time: NOW - 10 * DAY,
+      creator_user_id: 1,
+      subscription_kind: SubscriptionKind::Valid,
+      subscription_plan,
+      max_uses: limits.max_uses,
+      uses,
+      remaining_uses: limits.max_uses - uses,
+      period_start_time: NOW - 10 * DAY,
+      period_end_time: NOW - 10 * DAY + limits.period,
+      auto_renew,
+    }
+  }
+
+  fn usage(subscription: Subscription, seats: i64) -> SubscriptionUsage {
+    SubscriptionUsage {
+      subscription,
+      entries: vec![],
+      seats,
+    }
+  }
+
+  #[test]
+  fn plans_only_grow() {
+    let plans = [
+      SubscriptionPlan::Free,
+      SubscriptionPlan::Basic,
+      SubscriptionPlan::Standard,
+      SubscriptionPlan::Enterprise,
+    ];
+    for pair in plans.windows(2) {
+      let (a, b) = (plan_limits(&pair[0]), plan_limits(&pair[1]));
+      assert!(a.max_uses <= b.max_uses);
+      assert!(a.max_seats < b.max_seats);
+    }
+    for plan in plans.iter() {
+      let limits = plan_limits(plan);
+      assert!(limits.max_uses > 0 && limits.max_seats > 0 && limits.period > 0);
+    }
+  }
+
+  #[test]
+  fn current_period_without_renewal() {
+    let s = subscription(SubscriptionPlan::Free, 0, false);
+    let period = (s.period_start_time, s.period_end_time);
+    assert_eq!(current_period(&s, NOW), Some(period));
+    assert_eq!(current_period(&s, s.period_end_time - 1), Some(period));
+    assert_eq!(current_period(&s, s.period_end_time), None);
+  }
+
+  #[test]
+  fn current_period_with_renewal() {
+    let s = subscription(SubscriptionPlan::Free, 0, true);
+    let length = s.period_end_time - s.period_start_time;
+    assert_eq!(
+      current_period(&s, s.period_end_time),
+      Some((s.period_end_time, s.period_end_time + length))
+    );
+    assert_eq!(
+      current_period(&s, s.period_start_time + 3 * length + 1),
+      Some((
+        s.period_start_time + 3 * length,
+        s.period_start_time + 4 * length
+      ))
+    );
+
+    let mut empty = s.clone();
+    empty.period_end_time = empty.period_start_time;
+    assert_eq!(current_period(&empty, NOW), None);
+  }
+
+  #[test]
+  fn cancelled_or_lapsed_subscriptions_are_expired() {
+    let mut s = subscription(SubscriptionPlan::Basic, 0, false);
+    assert!(check_valid(&s, NOW).is_ok());
+    assert!(matches!(
+      check_valid(&s, s.period_end_time),
+      Err(InnexgoHoursError::SubscriptionExpired)
+    ));
+    s.subscription_kind = SubscriptionKind::Cancel;
+    assert!(matches!(
+      check_valid(&s, NOW),
+      Err(InnexgoHoursError::SubscriptionExpired)
+    ));
+  }
+
+  #[test]
+  fn school_new_uses_the_stored_limit() {
+    let max_uses = plan_limits(&SubscriptionPlan::Standard).max_uses;
+    let s = subscription(SubscriptionPlan::Standard, max_uses - 1, false);
+    assert!(check_school_new(&s, NOW).is_ok());
+    let s = subscription(SubscriptionPlan::Standard, max_uses, false);
+    assert!(matches!(
+      check_school_new(&s, NOW),
+      Err(InnexgoHoursError::SubscriptionLimited)
+    ));
+  }
+
+  #[test]
+  fn seat_new_uses_the_plan_limit() {
+    let max_seats = plan_limits(&SubscriptionPlan::Free).max_seats;
+    let s = subscription(SubscriptionPlan::Free, 1, false);
+    assert!(check_seat_new(&usage(s.clone(), max_seats - 1), NOW).is_ok());
+    assert!(matches!(
+      check_seat_new(&usage(s.clone(), max_seats), NOW),
+      Err(InnexgoHoursError::SubscriptionSeatsLimited)
+    ));
+    assert!(matches!(
+      check_seat_new(&usage(s.clone(), 0), s.period_end_time),
+      Err(InnexgoHoursError::SubscriptionExpired)
+    ));
+  }
+
+  #[test]
+  fn legacy_subscriptions_keep_their_capacity() {
+    let s: Subscription = serde_json::from_value(serde_json::json!({
+      "subscriptionId": 1,
+      "creationTime": 0,
+      "creatorUserId": 1,
+      "subscriptionKind": "VALID",
+      "maxUses": 3,
+      "uses": 2,
+      "remainingUses": 1,
+    }))
+    .unwrap();
+    assert!(matches!(s.subscription_plan, SubscriptionPlan::Legacy));
+    assert!(check_valid(&s, NOW).is_ok());
+    assert!(check_school_new(&s, NOW).is_ok());
+    assert!(check_seat_new(&usage(s.clone(), 10_000_000), NOW).is_ok());
+
+    let mut full = s.clone();
+    full.uses = 3;
+    assert!(matches!(
+      check_school_new(&full, NOW),
+      Err(InnexgoHoursError::SubscriptionLimited)
+    ));
+  }
+}
@@ -0,0 +1,182 @@
+// Signing and verification of webhook deliveries
+//
+// each delivery is POSTed with a header of the form `t=<timestamp>,v1=<signature>`
+// where signature is the hex encoded HMAC-SHA256 of `<timestamp>.<body>`,
+// keyed with the secret returned in the WebhookCredential when the webhook was created
+use super::response::{InnexgoHoursError, WebhookDelivery};
+use super::secret::Secret;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const SIGNATURE_HEADER: &str = "X-Innexgo-Hours-Signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac(secret: &Secret, timestamp: i64, body: &[u8]) -> HmacSha256 {
+  // hmac accepts keys of any length
+  let mut mac = HmacSha256::new_from_slice(secret.expose().as_bytes()).unwrap();
+  mac.update(timestamp.to_string().as_bytes());
+  mac.update(b".");
+  mac.update(body);
+  mac
+}
+
+// produces the value of the SIGNATURE_HEADER
+pub fn sign(secret: &Secret, timestamp: i64, body: &[u8]) -> String {
+  let signature = hex::encode(mac(secret, timestamp, body).finalize().into_bytes());
+  format!("t={},v1={}", timestamp, signature)
+}
+
+// checks the signature, then decodes the delivery
+// deliveries signed more than tolerance milliseconds away from now are rejected, to prevent replays
+pub fn verify(
+  secret: &Secret,
+  signature_header: &str,
+  body: &[u8],
+  now: i64,
+  tolerance: i64,
+) -> Result<WebhookDelivery, InnexgoHoursError> {
+  let mut timestamp = None;
+  let mut signature = None;
+  for part in signature_header.split(',') {
+    match part.trim().split_once('=') {
+      Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+      Some(("v1", v1)) => signature = hex::decode(v1).ok(),
+      _ => (),
+    }
+  }
+
+  let (timestamp, signature) = match (timestamp, signature) {
+    (Some(timestamp), Some(signature)) => (timestamp, signature),
+    _ => return Err(InnexgoHoursError::WebhookSignatureInvalid),
+  };
+
+  // constant time comparison
+  mac(secret, timestamp, body)
+    .verify_slice(&signature)
+    .map_err(|_| InnexgoHoursError::WebhookSignatureInvalid)?;
+
+  if (now - timestamp).abs() > tolerance {
+    return Err(InnexgoHoursError::WebhookSignatureExpired);
+  }
+
+  serde_json::from_slice(body).map_err(|_| InnexgoHoursError::DecodeError)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fixture;
+  use crate::response::{Commitment, WebhookEvent};
+
+  const NOW: i64 = 1_700_000_000_000;
+  const TOLERANCE: i64 = 5 * 60 * 1000;
+
+  fn secret() -> Secret {
+    Secret::from("whsec_test")
+  }
+
+  fn body() -> Vec<u8> {
+    let session_data = fixture::session_data(3, 2, NOW, NOW + 1000);
+    let delivery = WebhookDelivery {
+      webhook_id: 7,
+      creation_time: NOW,
+      event: WebhookEvent::CommitmentNew(fixture::commitment(5, &session_data)),
+    };
+    serde_json::to_vec(&delivery).unwrap()
+  }
+
+  #[test]
+  fn round_trip() {
+    let body = body();
+    let header = sign(&secret(), NOW, &body);
+    let delivery = verify(&secret(), &header, &body, NOW + 1000, TOLERANCE).unwrap();
+    assert_eq!(delivery.webhook_id, 7);
+    assert!(matches!(
+      delivery.event,
+      WebhookEvent::CommitmentNew(Commitment {
+        commitment_id: 503,
+        ..
+      })
+    ));
+  }
+
+  #[test]
+  fn tampered_body() {
+    let body = body();
+    let header = sign(&secret(), NOW, &body);
+    let mut tampered = body.clone();
+    let i = tampered.iter().position(|&b| b == b'7').unwrap();
+    tampered[i] = b'8';
+    assert!(matches!(
+      verify(&secret(), &header, &tampered, NOW, TOLERANCE),
+      Err(InnexgoHoursError::WebhookSignatureInvalid)
+    ));
+  }
+
+  #[test]
+  fn wrong_secret() {
+    let body = body();
+    let header = sign(&Secret::from("whsec_other"), NOW, &body);
+    assert!(matches!(
+      verify(&secret(), &header, &body, NOW, TOLERANCE),
+      Err(InnexgoHoursError::WebhookSignatureInvalid)
+    ));
+  }
+
+  #[test]
+  fn tampered_timestamp() {
+    let body = body();
+    let header = sign(&secret(), NOW, &body).replacen(&NOW.to_string(), &(NOW + 1).to_string(), 1);
+    assert!(matches!(
+      verify(&secret(), &header, &body, NOW, TOLERANCE),
+      Err(InnexgoHoursError::WebhookSignatureInvalid)
+    ));
+  }
+
+  #[test]
+  fn bad_header() {
+    let body = body();
+    let signature = sign(&secret(), NOW, &body);
+    let v1 = signature.split_once(",v1=").unwrap().1;
+    for header in [
+      String::new(),
+      String::from("garbage"),
+      format!("v1={}", v1),
+      format!("t={}", NOW),
+      format!("t=now,v1={}", v1),
+      format!("t={},v1=not-hex", NOW),
+    ] {
+      assert!(
+        matches!(
+          verify(&secret(), &header, &body, NOW, TOLERANCE),
+          Err(InnexgoHoursError::WebhookSignatureInvalid)
+        ),
+        "{}",
+        header
+      );
+    }
+  }
+
+  #[test]
+  fn expired_timestamp() {
+    let body = body();
+    let header = sign(&secret(), NOW, &body);
+    assert!(verify(&secret(), &header, &body, NOW + TOLERANCE, TOLERANCE).is_ok());
+    for now in [NOW + TOLERANCE + 1, NOW - TOLERANCE - 1] {
+      assert!(matches!(
+        verify(&secret(), &header, &body, now, TOLERANCE),
+        Err(InnexgoHoursError::WebhookSignatureExpired)
+      ));
+    }
+  }
+
+  #[test]
+  fn valid_signature_on_non_delivery() {
+    let header = sign(&secret(), NOW, b"{}");
+    assert!(matches!(
+      verify(&secret(), &header, b"{}", NOW, TOLERANCE),
+      Err(InnexgoHoursError::DecodeError)
+    ));
+  }
+}